@@ -0,0 +1,52 @@
+//! Compare the interpreter and the closure-compiled backend.
+//!
+//! Run with `cargo bench --bench intcode`.
+
+extern crate aoc;
+
+use std::time::{Duration, Instant};
+use aoc::intcode::{self, compiler, threaded, InputProvider, OutputCollector};
+
+type Backend = fn(&mut Vec<i64>, &mut InputProvider, &mut OutputCollector) -> Result<(), intcode::Error>;
+type Bench = fn(Backend);
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    // Best of several runs, after one to warm up
+    f();
+    (0..5).map(|_| {
+        let start = Instant::now();
+        f();
+        start.elapsed()
+    }).min().unwrap()
+}
+
+/// The day 2 noun/verb search, which runs the program ten thousand times
+fn day2(backend: Backend) {
+    let program = intcode::parse_input(include_str!("../input/2.txt")).unwrap();
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut p = program.clone();
+            p[1] = noun;
+            p[2] = verb;
+            backend(&mut p, &mut InputProvider::new(vec![]), &mut OutputCollector::new()).unwrap();
+        }
+    }
+}
+
+/// A long-running loop, where decoding dominates the interpreter
+fn squares(backend: Backend) {
+    let program = compiler::compile("n = input; t = 0; while n > 0 { t = t + n * n; n = n - 1; } output t;").unwrap();
+    let mut p = program.clone();
+    backend(&mut p, &mut InputProvider::new(vec![1_000_000]), &mut OutputCollector::new()).unwrap();
+}
+
+fn main() {
+    let benches: [(&str, Bench); 2] = [("day2 search", day2), ("sum of squares", squares)];
+    let backends: [(&str, Backend); 2] = [("interpreter", intcode::execute), ("threaded", threaded::execute)];
+
+    for (name, bench) in &benches {
+        for (backend_name, backend) in &backends {
+            println!("{:<16} {:<12} {:>10.2?}", name, backend_name, time(|| bench(*backend)));
+        }
+    }
+}
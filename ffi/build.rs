@@ -0,0 +1,175 @@
+//! Generates `include/intcode.h` from the exports in `src/lib.rs`.
+//!
+//! Each `pub struct` becomes an opaque typedef, each `pub const` a `#define`
+//! and each `extern "C" fn` a prototype, in the order they're written and
+//! under their doc comments up to any `# Safety` section. The header goes in
+//! `OUT_DIR`, and is copied into `include/` whenever that's out of date;
+//! `tests/header.rs` fails if the copy is stale.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const PREAMBLE: &str = "\
+/*
+ * C interface to the intcode machine, built as the intcode cdylib.
+ *
+ * A machine is created from an array of words and driven with
+ * intcode_run, which returns when the program needs input, outputs a
+ * value or halts. Outputs queue up until taken with intcode_pop_output.
+ * Every call accepts a null machine and fails harmlessly.
+ *
+ * Generated from src/lib.rs by build.rs: change those, not this.
+ */
+#ifndef INTCODE_H
+#define INTCODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const POSTAMBLE: &str = "\
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+";
+
+/// C types for the Rust types an export can use, besides its own structs
+const TYPES: [(&str, &str); 5] = [
+    ("i64", "int64_t"),
+    ("u64", "uint64_t"),
+    ("usize", "size_t"),
+    ("c_int", "int"),
+    ("c_char", "char")
+];
+
+fn c_type(rust: &str, structs: &[String]) -> String {
+    if let Some(pointee) = rust.strip_prefix("*const ") {
+        return format!("const {} *", c_type(pointee, structs));
+    }
+    if let Some(pointee) = rust.strip_prefix("*mut ") {
+        return format!("{} *", c_type(pointee, structs));
+    }
+    if structs.iter().any(|name| name == rust) {
+        return rust.to_string();
+    }
+    match TYPES.iter().find(|(from, _)| *from == rust) {
+        Some((_, to)) => to.to_string(),
+        None => panic!("src/lib.rs: no C type for `{}`", rust)
+    }
+}
+
+/// `name` declared as `ty`, with any `*` against the name
+fn declare(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') { format!("{}{}", ty, name) } else { format!("{} {}", ty, name) }
+}
+
+fn comment(doc: &[String]) -> String {
+    match doc {
+        [] => String::new(),
+        [line] => format!("/* {} */\n", line),
+        lines => {
+            let body: String = lines.iter()
+                .map(|line| if line.is_empty() { String::from(" *\n") } else { format!(" * {}\n", line) })
+                .collect();
+            format!("/*\n{} */\n", body)
+        }
+    }
+}
+
+/// The prototype for a function whose signature, up to its body, is `signature`
+fn prototype(signature: &str, structs: &[String]) -> String {
+    let signature = signature.split("extern \"C\" fn ").nth(1).expect("not a function");
+    let (name, rest) = signature.split_once('(').expect("no parameters");
+    let (params, rest) = rest.rsplit_once(')').expect("unclosed parameters");
+    let ret = rest.trim().strip_prefix("->").map_or(String::from("void"), |ret| c_type(ret.trim(), structs));
+
+    let params: Vec<String> = params.split(',').map(str::trim).filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_once(':').expect("parameter without a type");
+            declare(&c_type(ty.trim(), structs), name.trim())
+        })
+        .collect();
+    let params = if params.is_empty() { String::from("void") } else { params.join(", ") };
+    format!("{}({});", declare(&ret, name.trim()), params)
+}
+
+fn header(source: &str) -> String {
+    let mut items = Vec::new();
+    let mut structs = Vec::new();
+    let mut doc = Vec::new();
+    let mut in_safety = false;
+    let mut lines = source.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        if let Some(text) = line.strip_prefix("///") {
+            let text = text.trim().replace('`', "");
+            in_safety |= text.starts_with("# ");
+            if !in_safety {
+                doc.push(text);
+            }
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+
+        while doc.last().is_some_and(String::is_empty) {
+            doc.pop();
+        }
+        let declaration = if let Some(name) = line.strip_prefix("pub struct ") {
+            let name = name.trim_end_matches(" {").trim_end_matches(';').to_string();
+            let typedef = format!("typedef struct {0} {0};", name);
+            structs.push(name);
+            Some(typedef)
+        } else if let Some(constant) = line.strip_prefix("pub const ") {
+            let (name, value) = constant.split_once(':').expect("constant without a type");
+            let value = value.split_once('=').expect("constant without a value").1.trim().trim_end_matches(';');
+            let value = if value.starts_with('-') { format!("({})", value) } else { value.to_string() };
+            Some(format!("#define {} {}", name.trim(), value))
+        } else if line.contains("extern \"C\" fn ") {
+            let mut signature = String::from(line);
+            while !signature.contains('{') {
+                signature.push(' ');
+                signature.push_str(lines.next().expect("unfinished signature"));
+            }
+            let signature = signature.split('{').next().unwrap();
+            Some(prototype(signature, &structs))
+        } else {
+            None
+        };
+
+        if let Some(declaration) = declaration {
+            items.push(format!("{}{}\n", comment(&doc), declaration));
+        }
+        doc.clear();
+        in_safety = false;
+    }
+
+    format!("{}\n{}\n{}", PREAMBLE, items.join("\n"), POSTAMBLE)
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=include/intcode.h");
+
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let source = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    let header = header(&source);
+
+    let generated = PathBuf::from(env::var("OUT_DIR").unwrap()).join("intcode.h");
+    fs::write(generated, &header).unwrap();
+
+    // Left alone when it's current, so its timestamp only moves when it changes.
+    // A read-only checkout keeps its copy, and the test reports it if stale.
+    let copy = root.join("include/intcode.h");
+    if fs::read_to_string(&copy).ok().as_deref() != Some(header.as_str()) {
+        let _ = fs::write(copy, &header);
+    }
+}
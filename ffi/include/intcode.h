@@ -0,0 +1,82 @@
+/*
+ * C interface to the intcode machine, built as the intcode cdylib.
+ *
+ * A machine is created from an array of words and driven with
+ * intcode_run, which returns when the program needs input, outputs a
+ * value or halts. Outputs queue up until taken with intcode_pop_output.
+ * Every call accepts a null machine and fails harmlessly.
+ *
+ * Generated from src/lib.rs by build.rs: change those, not this.
+ */
+#ifndef INTCODE_H
+#define INTCODE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* intcode_run failed, and intcode_error says why */
+#define INTCODE_ERROR (-1)
+
+/* intcode_run needs input and the queue is empty */
+#define INTCODE_INPUT 1
+
+/* intcode_run queued an output */
+#define INTCODE_OUTPUT 2
+
+/* The program has halted */
+#define INTCODE_HALT 3
+
+/* A machine and what it's produced */
+typedef struct intcode_machine intcode_machine;
+
+/*
+ * A machine running a copy of len words at words, or null if the
+ * machine couldn't be made.
+ */
+intcode_machine *intcode_new(const int64_t *words, size_t len);
+
+/* Free a machine and everything it owns */
+void intcode_free(intcode_machine *machine);
+
+/*
+ * Queue a value for the program to read. Returns zero, or INTCODE_ERROR
+ * for a null machine.
+ */
+int intcode_push_input(intcode_machine *machine, int64_t value);
+
+/*
+ * Run until the program needs input, outputs a value or halts, returning
+ * the event's code, or INTCODE_ERROR if it failed.
+ */
+int intcode_run(intcode_machine *machine);
+
+/*
+ * Take the oldest output not yet taken. Returns 1 and stores it in value,
+ * or 0 if there's none.
+ */
+int intcode_pop_output(intcode_machine *machine, int64_t *value);
+
+/*
+ * The words of memory, storing how many in len. The pointer is valid
+ * until the machine next runs or is freed.
+ */
+const int64_t *intcode_memory(intcode_machine *machine, size_t *len);
+
+/* Instructions executed so far */
+uint64_t intcode_steps(intcode_machine *machine);
+
+/*
+ * What the last run failed with, or null if it didn't. The string belongs to
+ * the machine and is valid until it next runs or is freed.
+ */
+const char *intcode_error(intcode_machine *machine);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
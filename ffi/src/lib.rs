@@ -0,0 +1,189 @@
+//! A C interface to the intcode `Machine`, declared in `include/intcode.h`.
+//!
+//! A machine is an opaque pointer from `intcode_new`, freed with
+//! `intcode_free`. `intcode_run` returns one of the `INTCODE_*` event codes;
+//! outputs are queued for `intcode_pop_output`, and after `INTCODE_ERROR`
+//! the message is available from `intcode_error`. Every call accepts a null
+//! machine and fails harmlessly. No panic crosses the boundary: one is
+//! caught and reported as an error like any other.
+//!
+//! The header is generated from this file by `build.rs`, so the docs here
+//! up to any `# Safety` section are also the header's comments.
+
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use aoc::intcode::{Event, Machine};
+
+/// `intcode_run` failed, and `intcode_error` says why
+pub const INTCODE_ERROR: c_int = -1;
+/// `intcode_run` needs input and the queue is empty
+pub const INTCODE_INPUT: c_int = 1;
+/// `intcode_run` queued an output
+pub const INTCODE_OUTPUT: c_int = 2;
+/// The program has halted
+pub const INTCODE_HALT: c_int = 3;
+
+/// A machine and what it's produced
+#[allow(non_camel_case_types)]
+pub struct intcode_machine {
+    machine: Machine,
+    outputs: VecDeque<i64>,
+    /// The last error, kept so the pointer `intcode_error` hands out stays valid
+    error: Option<CString>
+}
+
+impl intcode_machine {
+    fn fail(&mut self, message: String) -> c_int {
+        self.error = CString::new(message.replace('\0', " ")).ok();
+        INTCODE_ERROR
+    }
+}
+
+fn panic_message(e: &(dyn std::any::Any + Send)) -> String {
+    let message = e.downcast_ref::<String>().map(String::as_str)
+        .or_else(|| e.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown");
+    format!("panic: {}", message)
+}
+
+/// Run `f` on `machine`, or return `default` if it's null
+unsafe fn with<T, F>(machine: *mut intcode_machine, default: T, f: F) -> T where
+    F: FnOnce(&mut intcode_machine) -> T
+{
+    match machine.as_mut() {
+        Some(machine) => f(machine),
+        None => default
+    }
+}
+
+/// A machine running a copy of `len` words at `words`, or null if the
+/// machine couldn't be made.
+///
+/// # Safety
+/// `words` must point to `len` readable words, or may be null if `len` is zero.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_new(words: *const i64, len: usize) -> *mut intcode_machine {
+    if words.is_null() && len > 0 {
+        return ptr::null_mut();
+    }
+    let program = if len == 0 { Vec::new() } else { slice::from_raw_parts(words, len).to_vec() };
+
+    panic::catch_unwind(|| {
+        Box::into_raw(Box::new(intcode_machine { machine: Machine::new(program), outputs: VecDeque::new(), error: None }))
+    }).unwrap_or(ptr::null_mut())
+}
+
+/// Free a machine and everything it owns
+///
+/// # Safety
+/// `machine` must come from `intcode_new` and not be used again. Null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_free(machine: *mut intcode_machine) {
+    if !machine.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(machine))));
+    }
+}
+
+/// Queue a value for the program to read. Returns zero, or `INTCODE_ERROR`
+/// for a null machine.
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_push_input(machine: *mut intcode_machine, value: i64) -> c_int {
+    with(machine, INTCODE_ERROR, |h| {
+        h.machine.push_input(value);
+        0
+    })
+}
+
+/// Run until the program needs input, outputs a value or halts, returning
+/// the event's code, or `INTCODE_ERROR` if it failed.
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_run(machine: *mut intcode_machine) -> c_int {
+    with(machine, INTCODE_ERROR, |h| {
+        h.error = None;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| loop {
+            match h.machine.run() {
+                Ok(Event::Input) => return Ok(INTCODE_INPUT),
+                Ok(Event::Output(value)) => {
+                    h.outputs.push_back(value);
+                    return Ok(INTCODE_OUTPUT);
+                },
+                Ok(Event::Pause) => {},
+                Ok(Event::Halt) => return Ok(INTCODE_HALT),
+                Err(e) => return Err(e.to_string())
+            };
+        }));
+        match result {
+            Ok(Ok(event)) => event,
+            Ok(Err(message)) => h.fail(message),
+            Err(e) => h.fail(panic_message(&*e))
+        }
+    })
+}
+
+/// Take the oldest output not yet taken. Returns 1 and stores it in `value`,
+/// or 0 if there's none.
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null, and `value` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_pop_output(machine: *mut intcode_machine, value: *mut i64) -> c_int {
+    if value.is_null() {
+        return 0;
+    }
+    with(machine, 0, |h| match h.outputs.pop_front() {
+        Some(output) => {
+            *value = output;
+            1
+        },
+        None => 0
+    })
+}
+
+/// The words of memory, storing how many in `len`. The pointer is valid
+/// until the machine next runs or is freed.
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null, and `len` must be
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_memory(machine: *mut intcode_machine, len: *mut usize) -> *const i64 {
+    let memory = match machine.as_ref() {
+        Some(h) => h.machine.memory(),
+        None => &[]
+    };
+    if !len.is_null() {
+        *len = memory.len();
+    }
+    memory.as_ptr()
+}
+
+/// Instructions executed so far
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_steps(machine: *mut intcode_machine) -> u64 {
+    with(machine, 0, |h| h.machine.steps())
+}
+
+/// What the last run failed with, or null if it didn't. The string belongs to
+/// the machine and is valid until it next runs or is freed.
+///
+/// # Safety
+/// `machine` must come from `intcode_new`, or be null.
+#[no_mangle]
+pub unsafe extern "C" fn intcode_error(machine: *mut intcode_machine) -> *const c_char {
+    with(machine, ptr::null(), |h| h.error.as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
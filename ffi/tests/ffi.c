@@ -0,0 +1,74 @@
+/* Exercises the C interface; built and run by tests/ffi.rs */
+#include <stdio.h>
+#include <string.h>
+
+#include "intcode.h"
+
+static int failures = 0;
+
+#define CHECK(cond) do { \
+    if (!(cond)) { \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+        failures++; \
+    } \
+} while (0)
+
+static void adds_one(void)
+{
+    const int64_t program[] = {3,9, 1001,9,1,9, 4,9, 99, 0};
+    intcode_machine *m = intcode_new(program, sizeof program / sizeof *program);
+    int64_t value = 0;
+    size_t len = 0;
+
+    CHECK(m != NULL);
+    CHECK(intcode_run(m) == INTCODE_INPUT);
+    CHECK(intcode_push_input(m, 41) == 0);
+    CHECK(intcode_run(m) == INTCODE_OUTPUT);
+    CHECK(intcode_pop_output(m, &value) == 1 && value == 42);
+    CHECK(intcode_pop_output(m, &value) == 0);
+    CHECK(intcode_run(m) == INTCODE_HALT);
+    CHECK(intcode_steps(m) == 4);
+
+    const int64_t *memory = intcode_memory(m, &len);
+    CHECK(len == 10 && memory[9] == 42);
+    CHECK(intcode_error(m) == NULL);
+    intcode_free(m);
+}
+
+static void reports_errors(void)
+{
+    const int64_t program[] = {42};
+    intcode_machine *m = intcode_new(program, 1);
+
+    CHECK(intcode_run(m) == INTCODE_ERROR);
+    CHECK(intcode_error(m) != NULL && strcmp(intcode_error(m), "Unknown opcode 42 at 0") == 0);
+    intcode_free(m);
+}
+
+static void tolerates_null(void)
+{
+    int64_t value;
+    size_t len = 1;
+
+    CHECK(intcode_new(NULL, 3) == NULL);
+    CHECK(intcode_run(NULL) == INTCODE_ERROR);
+    CHECK(intcode_push_input(NULL, 1) == INTCODE_ERROR);
+    CHECK(intcode_pop_output(NULL, &value) == 0);
+    intcode_memory(NULL, &len);
+    CHECK(len == 0);
+    CHECK(intcode_error(NULL) == NULL);
+    intcode_free(NULL);
+
+    /* An empty program halts straight away */
+    intcode_machine *m = intcode_new(NULL, 0);
+    CHECK(intcode_run(m) == INTCODE_HALT);
+    intcode_free(m);
+}
+
+int main(void)
+{
+    adds_one();
+    reports_errors();
+    tolerates_null();
+    return failures == 0 ? 0 : 1;
+}
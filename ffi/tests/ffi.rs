@@ -0,0 +1,49 @@
+//! Builds `tests/ffi.c` against `include/intcode.h` and the cdylib, and runs
+//! it, and checks what the C side can't: that a panic stops at the boundary.
+
+use std::env;
+use std::ffi::CStr;
+use std::path::PathBuf;
+use std::process::Command;
+
+use aoc::intcode::opcode::{self, Effect, Opcode};
+use intcode::*;
+
+#[cfg(unix)]
+#[test]
+fn c_program() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Tests are built in target/<profile>/deps, next to the library
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let library = ["libintcode.so", "libintcode.dylib"].iter().map(|name| deps.join(name)).find(|path| path.exists())
+        .expect("cdylib not built");
+
+    let exe = deps.join("ffi-c");
+    let compiler = env::var("CC").unwrap_or_else(|_| String::from("cc"));
+    let status = Command::new(compiler)
+        .args(["-std=c99", "-Wall", "-Werror", "-o"]).arg(&exe)
+        .arg("-I").arg(root.join("include"))
+        .arg(root.join("tests/ffi.c"))
+        .arg(&library)
+        .arg(format!("-Wl,-rpath,{}", deps.display()))
+        .status().expect("Couldn't run the C compiler");
+    assert!(status.success(), "Compiling tests/ffi.c failed");
+
+    let status = Command::new(&exe).status().unwrap();
+    assert!(status.success(), "tests/ffi.c failed");
+}
+
+#[test]
+fn panic_is_an_error() {
+    opcode::register(Opcode { code: 77, mnemonic: "boom", params: &[], semantics: |_| -> Effect { panic!("boom") } })
+        .unwrap();
+
+    let program = [77, 99];
+    unsafe {
+        let machine = intcode_new(program.as_ptr(), program.len());
+        assert!(!machine.is_null());
+        assert_eq!(intcode_run(machine), INTCODE_ERROR);
+        assert_eq!(CStr::from_ptr(intcode_error(machine)).to_str(), Ok("panic: boom"));
+        intcode_free(machine);
+    }
+}
@@ -0,0 +1,33 @@
+//! Checks `include/intcode.h` is the header `build.rs` generates from
+//! `src/lib.rs`, and that its event codes are the library's.
+
+use std::path::PathBuf;
+
+fn checked_in() -> String {
+    std::fs::read_to_string(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("include/intcode.h")).unwrap()
+}
+
+#[test]
+fn header_is_current() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/intcode.h"));
+    assert!(checked_in() == generated, "include/intcode.h is stale; build the crate to regenerate it");
+}
+
+#[test]
+fn constants_match() {
+    let header = checked_in();
+    let defined: Vec<(&str, i32)> = header.lines()
+        .filter_map(|line| line.strip_prefix("#define "))
+        .filter_map(|define| {
+            let (name, value) = define.split_once(' ')?;
+            Some((name, value.trim().trim_start_matches('(').trim_end_matches(')').parse().ok()?))
+        })
+        .collect();
+
+    assert_eq!(defined, vec![
+        ("INTCODE_ERROR", intcode::INTCODE_ERROR),
+        ("INTCODE_INPUT", intcode::INTCODE_INPUT),
+        ("INTCODE_OUTPUT", intcode::INTCODE_OUTPUT),
+        ("INTCODE_HALT", intcode::INTCODE_HALT)
+    ]);
+}
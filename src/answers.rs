@@ -0,0 +1,173 @@
+//! The answers we've had accepted, to check solutions against.
+//!
+//! An answers file has one `<day> <part> <input> <answer>` line per known
+//! answer, where `input` is relative to the file. `#` starts a comment.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::solution::Day;
+use crate::{inputs, Error, SubProblem};
+
+/// The answers file kept with the code
+pub const DEFAULT_FILE: &str = "answers.txt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expected {
+    pub day: u32,
+    pub part: SubProblem,
+    pub input: PathBuf,
+    pub answer: String
+}
+
+pub fn parse(text: &str) -> Result<Vec<Expected>, String> {
+    let mut expected = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let error = || format!("line {}: expected `<day> <part> <input> <answer>`, found `{}`", n + 1, line);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() != 4 {
+            return Err(error());
+        }
+
+        let part = match words[1] {
+            "1" => SubProblem::One,
+            "2" => SubProblem::Two,
+            _ => return Err(error())
+        };
+        expected.push(Expected {
+            day: words[0].parse().map_err(|_| error())?,
+            part,
+            input: PathBuf::from(words[2]),
+            answer: String::from(words[3])
+        });
+    }
+
+    Ok(expected)
+}
+
+/// Read an answers file, resolving inputs against its directory
+pub fn load(path: &Path) -> Result<Vec<Expected>, Error> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut expected = parse(&inputs::read(path)?)
+        .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+
+    for e in &mut expected {
+        e.input = dir.join(&e.input);
+    }
+    Ok(expected)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Pass,
+    /// What we got instead, or why we got nothing
+    Fail(String),
+    /// A solved part with no answer to check against
+    Missing
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Pass => f.pad("pass"),
+            Status::Fail(_) => f.pad("FAIL"),
+            Status::Missing => f.pad("missing")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub day: u32,
+    pub part: SubProblem,
+    pub input: Option<PathBuf>,
+    pub status: Status
+}
+
+fn check(day: &Day, expected: &Expected) -> Status {
+    let solved = inputs::read(&expected.input).and_then(|input| day.solve(&input, &expected.part));
+    match solved {
+        Ok(ref answer) if *answer == expected.answer => Status::Pass,
+        Ok(answer) => Status::Fail(format!("expected {}, got {}", expected.answer, answer)),
+        Err(e) => Status::Fail(e.to_string())
+    }
+}
+
+/// Check every expected answer, and note every implemented part of `days`
+/// that has none
+pub fn verify(days: &[Day], expected: &[Expected]) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    for day in days {
+        for part in [SubProblem::One, SubProblem::Two] {
+            let mut found = false;
+            for e in expected.iter().filter(|e| e.day == day.number && e.part == part) {
+                found = true;
+                checks.push(Check { day: day.number, part, input: Some(e.input.clone()), status: check(day, e) });
+            }
+
+            if !found && day.implements(&part) {
+                checks.push(Check { day: day.number, part, input: None, status: Status::Missing });
+            }
+        }
+    }
+
+    // Answers for days we can no longer solve
+    for e in expected.iter().filter(|e| !days.iter().any(|d| d.number == e.day)) {
+        let status = Status::Fail(format!("Day {} isn't solved", e.day));
+        checks.push(Check { day: e.day, part: e.part, input: Some(e.input.clone()), status });
+    }
+
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::DAYS;
+
+    #[test]
+    fn parsing() {
+        let expected = parse("# day part input answer\n1 1 input/1.txt 3271095\n\n6 2 input/6.txt 42 # comment\n").unwrap();
+        assert_eq!(expected, vec![
+            Expected { day: 1, part: SubProblem::One, input: PathBuf::from("input/1.txt"), answer: String::from("3271095") },
+            Expected { day: 6, part: SubProblem::Two, input: PathBuf::from("input/6.txt"), answer: String::from("42") }
+        ]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("1 1 input/1.txt"),
+                   Err(String::from("line 1: expected `<day> <part> <input> <answer>`, found `1 1 input/1.txt`")));
+        assert!(parse("1 3 input/1.txt 5").is_err());
+        assert!(parse("one 1 input/1.txt 5").is_err());
+    }
+
+    #[test]
+    fn verifying() {
+        let expected = parse("1 1 input/1.txt 3271095\n1 2 input/1.txt 1\n9 1 input/9.txt 1\n").unwrap();
+        let checks = verify(&DAYS[..1], &expected);
+
+        let statuses: Vec<_> = checks.iter().map(|c| (c.day, c.part, c.status.to_string())).collect();
+        assert_eq!(statuses, vec![
+            (1, SubProblem::One, String::from("pass")),
+            (1, SubProblem::Two, String::from("FAIL")),
+            (9, SubProblem::One, String::from("FAIL"))
+        ]);
+        assert_eq!(checks[1].status, Status::Fail(String::from("expected 1, got 4903759")));
+    }
+
+    #[test]
+    fn missing_answers() {
+        // Day 6 part two isn't implemented, so only part one is missing
+        let checks = verify(&DAYS[5..6], &[]);
+        assert_eq!(checks.len(), 1);
+        assert_eq!((checks[0].part, &checks[0].status), (SubProblem::One, &Status::Missing));
+    }
+}
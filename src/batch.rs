@@ -0,0 +1,195 @@
+//! Solving one day for many inputs at once.
+//!
+//! Inputs are named by path, directory or glob, and solved in parallel on a
+//! few threads. Each file's answers come back in the order the files were
+//! named, with what each took and whatever went wrong.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::json::Value;
+use crate::solution::{Day, Report};
+use crate::{inputs, Error, SubProblem};
+
+/// One part of one input
+#[derive(Debug)]
+pub struct Outcome {
+    pub input: PathBuf,
+    pub report: Report
+}
+
+/// Whether `name` matches `pattern`, where `*` is any run of characters
+/// and `?` any one
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // The last `*` seen, and how much of `name` it has taken
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            },
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            // Let the last `*` take one more character and try again
+            _ => match star {
+                Some((at, taken)) => {
+                    star = Some((at, taken + 1));
+                    p = at + 1;
+                    n = taken + 1;
+                },
+                None => return false
+            }
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn files_in(dir: &Path, keep: impl Fn(&str) -> bool) -> Result<Vec<PathBuf>, Error> {
+    let io = |e| Error::Io(dir.to_path_buf(), e);
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(io)? {
+        let entry = entry.map_err(io)?;
+        let name = entry.file_name();
+        if entry.file_type().map_err(io)?.is_file() && keep(&name.to_string_lossy()) {
+            files.push(entry.path());
+        }
+    }
+    if files.is_empty() {
+        return Err(Error::Io(dir.to_path_buf(), io::Error::new(io::ErrorKind::NotFound, "no input files")));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The input files `path` names: itself, the files in it if it's a
+/// directory, or the files matching it if its last part is a glob. It's an
+/// error for a directory or glob to name no files.
+pub fn expand(path: &Path) -> Result<Vec<PathBuf>, Error> {
+    if path.is_dir() {
+        return files_in(path, |_| true);
+    }
+
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if dir != Path::new("") => dir,
+        _ => Path::new(".")
+    };
+    let pattern: Vec<char> = name.chars().collect();
+    files_in(dir, |file| matches(&pattern, &file.chars().collect::<Vec<_>>())).map_err(|e| match e {
+        Error::Io(_, e) if e.kind() == io::ErrorKind::NotFound => Error::Io(path.to_path_buf(), e),
+        e => e
+    })
+}
+
+fn solve_file(day: &Day, input: &Path, parts: &[SubProblem]) -> Vec<Outcome> {
+    let text = inputs::read(input);
+    parts.iter().map(|part| {
+        let report = match &text {
+            Ok(text) => day.report(text, part),
+            Err(e) => Report { day: day.number, part: *part, answer: Err(e.clone()), time: Duration::default() }
+        };
+        Outcome { input: input.to_path_buf(), report }
+    }).collect()
+}
+
+/// Solve `parts` of every input on up to `jobs` threads
+pub fn solve(day: &Day, inputs: &[PathBuf], parts: &[SubProblem], jobs: usize) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<(usize, Vec<Outcome>)>> = Mutex::new(Vec::with_capacity(inputs.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, inputs.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                match inputs.get(i) {
+                    Some(input) => {
+                        let outcomes = solve_file(day, input, parts);
+                        results.lock().unwrap().push((i, outcomes));
+                    },
+                    None => break
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().flat_map(|(_, outcomes)| outcomes).collect()
+}
+
+pub fn to_json(outcomes: &[Outcome]) -> Value {
+    Value::Array(outcomes.iter().map(|o| match o.report.to_json() {
+        Value::Object(mut members) => {
+            members.insert(2, (String::from("input"), Value::from(o.input.display().to_string())));
+            Value::Object(members)
+        },
+        other => other
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::day;
+
+    fn glob(pattern: &str, name: &str) -> bool {
+        matches(&pattern.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn globbing() {
+        assert!(glob("*.txt", "1.txt"));
+        assert!(glob("*.txt", ".txt"));
+        assert!(glob("day?-*", "day3-alice"));
+        assert!(!glob("*.txt", "1.txt.bak"));
+        assert!(!glob("day?", "day10"));
+        assert!(glob("a*b*c", "aXbYbZc"));
+        assert!(glob("*", ""));
+        assert!(glob("a**", "a"));
+        assert!(!glob("*a*a*a*a*a*a*a*a*a*a*b", &"a".repeat(60)));
+    }
+
+    #[test]
+    fn expanding() {
+        let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("input");
+        assert_eq!(expand(&input.join("1.txt")).unwrap(), vec![input.join("1.txt")]);
+        assert_eq!(expand(&input.join("?.txt")).unwrap().len(), 7);
+        assert_eq!(expand(&input).unwrap(), expand(&input.join("*")).unwrap());
+        assert_eq!(expand(&input.join("1*")).unwrap(), vec![input.join("1.txt")]);
+        assert!(matches!(expand(&input.join("*.json")), Err(Error::Io(path, _)) if path == input.join("*.json")));
+    }
+
+    #[test]
+    fn solving_in_order() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("input");
+        let inputs = vec![dir.join("1.txt"), dir.join("missing.txt"), dir.join("2.txt"), dir.join("1.txt")];
+        let outcomes = solve(day(1).unwrap(), &inputs, &[SubProblem::One, SubProblem::Two], 3);
+
+        let answers: Vec<_> = outcomes.iter().map(|o| o.report.answer.as_ref().ok().cloned()).collect();
+        assert_eq!(answers, vec![
+            Some(String::from("3271095")), Some(String::from("4903759")),
+            None, None,
+            None, None,
+            Some(String::from("3271095")), Some(String::from("4903759"))
+        ]);
+        assert!(matches!(outcomes[2].report.answer, Err(Error::Io(..))));
+        assert!(matches!(outcomes[4].report.answer, Err(Error::Parse(_))));
+        assert_eq!(outcomes[7].report.part, SubProblem::Two);
+
+        let json = to_json(&outcomes[2..3]);
+        assert_eq!(json.as_array().unwrap()[0].get("input").and_then(Value::as_str), Some(inputs[1].to_str().unwrap()));
+    }
+}
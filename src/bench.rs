@@ -0,0 +1,241 @@
+//! Timing each day's parse step and parts.
+//!
+//! Every stage is run a few times to warm up, then timed over repeated runs.
+//! Results can be saved as JSON and compared against a later run, flagging
+//! stages whose median got slower.
+
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::json::Value;
+use crate::solution::{self, Solution};
+use crate::{Error, SubProblem};
+
+/// How many times to run each stage
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub warmup: usize,
+    pub runs: usize
+}
+
+impl Config {
+    /// The runs timed, which is at least one
+    pub fn samples(&self) -> usize {
+        self.runs.max(1)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config { warmup: 2, runs: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Parse,
+    Part(SubProblem)
+}
+
+impl Stage {
+    fn from_name(name: &str) -> Option<Stage> {
+        match name {
+            "parse" => Some(Stage::Parse),
+            "part1" => Some(Stage::Part(SubProblem::One)),
+            "part2" => Some(Stage::Part(SubProblem::Two)),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part(part) => write!(f, "part{}", part)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration
+}
+
+impl Stats {
+    /// Summarise at least one sample
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "No samples to summarise");
+        samples.sort();
+
+        // Nearest rank
+        let rank = |p: usize| samples[(samples.len() * p).div_ceil(100).max(1) - 1];
+        Stats { min: samples[0], median: rank(50), p95: rank(95) }
+    }
+}
+
+/// Each stage a day timed, in order
+pub type Timings = Vec<(Stage, Stats)>;
+
+/// One stage of one day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub day: u32,
+    pub stage: Stage,
+    pub stats: Stats
+}
+
+fn time<T, F: FnMut() -> T>(config: &Config, mut f: F) -> Stats {
+    for _ in 0..config.warmup {
+        black_box(f());
+    }
+    Stats::from_samples((0..config.samples()).map(|_| {
+        let start = Instant::now();
+        black_box(f());
+        start.elapsed()
+    }).collect())
+}
+
+/// Time parsing `input` as `S`, then each part it implements
+pub fn measure<S: Solution>(input: &str, config: &Config) -> Result<Timings, Error> {
+    let input = &*solution::prepare::<S>(input);
+    let puzzle = S::parse(input).map_err(Error::Parse)?;
+    let mut stats = vec![(Stage::Parse, time(config, || S::parse(input)))];
+
+    // Check each part succeeds before timing it
+    puzzle.part1().map_err(Error::NoSolution)?;
+    stats.push((Stage::Part(SubProblem::One), time(config, || puzzle.part1())));
+
+    if S::PART2 {
+        puzzle.part2().map_err(Error::NoSolution)?;
+        stats.push((Stage::Part(SubProblem::Two), time(config, || puzzle.part2())));
+    }
+
+    Ok(stats)
+}
+
+fn nanos(d: Duration) -> Value {
+    Value::Number(d.as_nanos() as f64)
+}
+
+pub fn to_json(config: &Config, measurements: &[Measurement]) -> Value {
+    Value::object(vec![
+        ("warmup", Value::Number(config.warmup as f64)),
+        ("runs", Value::Number(config.samples() as f64)),
+        ("results", Value::Array(measurements.iter().map(|m| Value::object(vec![
+            ("day", Value::from(m.day)),
+            ("stage", Value::from(m.stage.to_string())),
+            ("min_ns", nanos(m.stats.min)),
+            ("median_ns", nanos(m.stats.median)),
+            ("p95_ns", nanos(m.stats.p95))
+        ])).collect()))
+    ])
+}
+
+pub fn from_json(value: &Value) -> Result<Vec<Measurement>, String> {
+    let results = value.get("results").and_then(Value::as_array).ok_or("No results array")?;
+
+    results.iter().map(|r| {
+        let number = |key: &str| r.get(key).and_then(Value::as_f64).ok_or(format!("Result has no `{}`", key));
+        let duration = |key: &str| number(key).map(|n| Duration::from_nanos(n as u64));
+
+        let stage = r.get("stage").and_then(Value::as_str).ok_or("Result has no `stage`")?;
+        Ok(Measurement {
+            day: number("day")? as u32,
+            stage: Stage::from_name(stage).ok_or(format!("Unknown stage `{}`", stage))?,
+            stats: Stats { min: duration("min_ns")?, median: duration("median_ns")?, p95: duration("p95_ns")? }
+        })
+    }).collect()
+}
+
+/// A stage whose median got slower by more than the threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub day: u32,
+    pub stage: Stage,
+    pub before: Duration,
+    pub after: Duration
+}
+
+impl Regression {
+    /// How much slower, as a percentage, or `None` if the baseline took no
+    /// time at all
+    pub fn percent(&self) -> Option<f64> {
+        if self.before.is_zero() {
+            return None;
+        }
+        Some((self.after.as_secs_f64() / self.before.as_secs_f64() - 1.0) * 100.0)
+    }
+}
+
+/// Stages in both runs whose median grew by more than `threshold` percent,
+/// or at all from zero
+pub fn compare(baseline: &[Measurement], current: &[Measurement], threshold: f64) -> Vec<Regression> {
+    current.iter().filter_map(|m| {
+        let before = baseline.iter().find(|b| b.day == m.day && b.stage == m.stage)?;
+        let regression = Regression { day: m.day, stage: m.stage, before: before.stats.median, after: m.stats.median };
+        let slower = match regression.percent() {
+            Some(percent) => percent > threshold,
+            None => !regression.after.is_zero()
+        };
+        if slower { Some(regression) } else { None }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn stats() {
+        let stats = Stats::from_samples((1..=20).rev().map(ms).collect());
+        assert_eq!(stats, Stats { min: ms(1), median: ms(10), p95: ms(19) });
+
+        let one = Stats::from_samples(vec![ms(7)]);
+        assert_eq!(one, Stats { min: ms(7), median: ms(7), p95: ms(7) });
+    }
+
+    #[test]
+    fn measuring() {
+        let config = Config { warmup: 1, runs: 3 };
+        let stages: Vec<Stage> = measure::<crate::day1::Modules>("12\n1969\n", &config).unwrap()
+            .into_iter().map(|(stage, _)| stage).collect();
+        assert_eq!(stages, vec![Stage::Parse, Stage::Part(SubProblem::One), Stage::Part(SubProblem::Two)]);
+
+        // Part two of day 6 isn't timed
+        assert_eq!(measure::<crate::day6::Orbits>("COM)B\n", &config).unwrap().len(), 2);
+        assert!(matches!(measure::<crate::day1::Modules>("x", &config), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let measurements = vec![
+            Measurement { day: 2, stage: Stage::Part(SubProblem::Two), stats: Stats { min: ms(1), median: ms(2), p95: ms(3) } }
+        ];
+        let json = to_json(&Config::default(), &measurements).to_string();
+        assert_eq!(from_json(&Value::parse(&json).unwrap()), Ok(measurements));
+
+        // The runs made, not the runs asked for
+        let json = to_json(&Config { warmup: 0, runs: 0 }, &[]);
+        assert_eq!(json.get("runs").and_then(Value::as_f64), Some(1.0));
+    }
+
+    #[test]
+    fn regressions() {
+        let run = |day, median| Measurement { day, stage: Stage::Parse, stats: Stats { min: ms(1), median: ms(median), p95: ms(median) } };
+        let baseline = vec![run(1, 100), run(2, 100), run(4, 0), run(5, 0)];
+        let current = vec![run(1, 105), run(2, 150), run(3, 500), run(4, 0), run(5, 1)];
+
+        let found = compare(&baseline, &current, 10.0);
+        assert_eq!(found.iter().map(|r| r.day).collect::<Vec<_>>(), vec![2, 5]);
+        assert!((found[0].percent().unwrap() - 50.0).abs() < 1e-9);
+        assert_eq!(found[1].percent(), None);
+    }
+}
@@ -0,0 +1,249 @@
+extern crate aoc;
+
+use std::path::{Path, PathBuf};
+use std::thread;
+use structopt::StructOpt;
+use aoc::{answers, batch, bench, inputs, json, scaffold, solution, Error, Format, Parts};
+
+/// Advent of Code 2019
+#[derive(StructOpt,Debug)]
+enum Opts {
+    /// Solve a day's puzzle
+    Run {
+        /// Day to solve
+        day: u32,
+
+        /// Part to answer: 1, 2 or all
+        #[structopt(short, long, default_value = "all")]
+        part: Parts,
+
+        /// Path to problem input, or `-` for stdin [default: <input-dir>/<day>.txt]
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+
+        /// Directory of inputs named by day
+        #[structopt(long, env = "AOC_INPUT_DIR", default_value = inputs::DEFAULT_DIR, parse(from_os_str))]
+        input_dir: PathBuf,
+
+        /// Output format: table or json
+        #[structopt(long, default_value = "table")]
+        format: Format,
+    },
+
+    /// Time parsing and each part of one day, or all of them
+    Bench {
+        /// Day to time [default: every day]
+        day: Option<u32>,
+
+        /// Untimed runs of each stage first
+        #[structopt(long, default_value = "2")]
+        warmup: usize,
+
+        /// Timed runs of each stage
+        #[structopt(long, default_value = "10")]
+        runs: usize,
+
+        /// Save the results as JSON
+        #[structopt(long, parse(from_os_str))]
+        save: Option<PathBuf>,
+
+        /// Flag stages slower than in these saved results, failing if any are
+        #[structopt(long, parse(from_os_str))]
+        compare: Option<PathBuf>,
+
+        /// Percentage a median may grow by before it's flagged
+        #[structopt(long, default_value = "10")]
+        threshold: f64,
+
+        /// Directory of inputs named by day
+        #[structopt(long, env = "AOC_INPUT_DIR", default_value = inputs::DEFAULT_DIR, parse(from_os_str))]
+        input_dir: PathBuf,
+    },
+
+    /// Solve a day for many inputs in parallel
+    Batch {
+        /// Day to solve
+        day: u32,
+
+        /// Input files, directories of them, or globs like `inputs/day3-*`
+        #[structopt(required = true, parse(from_os_str))]
+        inputs: Vec<PathBuf>,
+
+        /// Part to answer: 1, 2 or all
+        #[structopt(short, long, default_value = "all")]
+        part: Parts,
+
+        /// Inputs to solve at once [default: one per CPU]
+        #[structopt(short, long)]
+        jobs: Option<usize>,
+
+        /// Output format: table or json
+        #[structopt(long, default_value = "table")]
+        format: Format,
+    },
+
+    /// Add a new day's solver module, registry entry, binary and input file
+    New {
+        /// Day to add
+        day: u32,
+
+        /// The puzzle's title [default: Day <day>]
+        #[structopt(long)]
+        title: Option<String>,
+
+        /// The crate to add it to
+        #[structopt(long, default_value = ".", parse(from_os_str))]
+        root: PathBuf,
+    },
+
+    /// Check every solution against the answers we know
+    Verify {
+        /// Day to check [default: every day]
+        day: Option<u32>,
+
+        /// File of known answers
+        #[structopt(long, default_value = answers::DEFAULT_FILE, parse(from_os_str))]
+        answers: PathBuf,
+    },
+}
+
+fn find(number: u32) -> &'static solution::Day {
+    match solution::day(number) {
+        Some(d) => d,
+        None => Error::NoSolution(format!("Day {} isn't solved", number)).exit()
+    }
+}
+
+fn run(number: u32, part: Parts, input: Option<PathBuf>, input_dir: &Path, format: Format) -> ! {
+    let day = find(number);
+    let input = inputs::read(&inputs::resolve(input, input_dir, number)).unwrap_or_else(|e| e.exit());
+    solution::print(day, &input, &day.parts(part), format)
+}
+
+fn load_baseline(path: &Path) -> Result<Vec<bench::Measurement>, Error> {
+    let text = inputs::read(path)?;
+    json::Value::parse(&text).and_then(|v| bench::from_json(&v))
+        .map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))
+}
+
+fn time(days: &[&solution::Day], config: &bench::Config, input_dir: &Path) -> Result<Vec<bench::Measurement>, Error> {
+    let mut measurements = Vec::new();
+    for day in days {
+        let input = inputs::read(&inputs::resolve(None, input_dir, day.number))?;
+        for m in day.bench(&input, config)? {
+            println!("{:>3} {:<6} min {:>10.2?}  median {:>10.2?}  p95 {:>10.2?}",
+                     m.day, m.stage, m.stats.min, m.stats.median, m.stats.p95);
+            measurements.push(m);
+        }
+    }
+    Ok(measurements)
+}
+
+/// Print each input's answers, returning whether all were solved
+fn batch(outcomes: &[batch::Outcome], format: Format) -> bool {
+    match format {
+        Format::Json => println!("{}", batch::to_json(outcomes)),
+        Format::Table => {
+            println!("part {:>12}  input", "time");
+            for o in outcomes {
+                let result = match &o.report.answer {
+                    Ok(answer) => answer.clone(),
+                    Err(e) => format!("error: {}", e)
+                };
+                println!("{:>4} {:>12.2?}  {}: {}", o.report.part, o.report.time, o.input.display(), result);
+            }
+        }
+    }
+    outcomes.iter().all(|o| o.report.answer.is_ok())
+}
+
+/// Print a table of checks, returning whether none failed
+fn verify(days: &[solution::Day], expected: &[answers::Expected]) -> bool {
+    let checks = answers::verify(days, expected);
+
+    println!("day part  status   input");
+    for c in &checks {
+        let input = c.input.as_ref().map_or(String::from("-"), |p| p.display().to_string());
+        match &c.status {
+            answers::Status::Fail(why) => println!("{:>3} {:>4}  {:<8} {}: {}", c.day, c.part, c.status, input, why),
+            status => println!("{:>3} {:>4}  {:<8} {}", c.day, c.part, status, input)
+        }
+    }
+
+    let count = |f: fn(&answers::Status) -> bool| checks.iter().filter(|c| f(&c.status)).count();
+    let failed = count(|s| matches!(s, answers::Status::Fail(_)));
+    println!("{} passed, {} failed, {} missing",
+             count(|s| *s == answers::Status::Pass), failed, count(|s| *s == answers::Status::Missing));
+    failed == 0
+}
+
+fn main()
+{
+    match Opts::from_args() {
+        Opts::Run { day, part, input, input_dir, format } => run(day, part, input, &input_dir, format),
+        Opts::Bench { day, warmup, runs, save, compare, threshold, input_dir } => {
+            let days: Vec<&solution::Day> = match day {
+                Some(number) => vec![find(number)],
+                None => solution::DAYS.iter().collect()
+            };
+            let baseline = compare.map(|path| load_baseline(&path).unwrap_or_else(|e| e.exit()));
+
+            let config = bench::Config { warmup, runs };
+            let measurements = time(&days, &config, &input_dir).unwrap_or_else(|e| e.exit());
+
+            if let Some(path) = save {
+                if let Err(e) = std::fs::write(&path, bench::to_json(&config, &measurements).to_string()) {
+                    Error::Io(path, e).exit();
+                }
+            }
+
+            if let Some(baseline) = baseline {
+                let regressions = bench::compare(&baseline, &measurements, threshold);
+                for r in &regressions {
+                    let change = r.percent().map_or(String::from("from zero"), |p| format!("+{:.0}%", p));
+                    println!("Regression: day {} {} median {:.2?} -> {:.2?} ({})",
+                             r.day, r.stage, r.before, r.after, change);
+                }
+                if !regressions.is_empty() {
+                    std::process::exit(1);
+                }
+                println!("No regressions beyond {}%", threshold);
+            }
+        },
+        Opts::Batch { day: number, inputs: patterns, part, jobs, format } => {
+            let day = find(number);
+
+            let mut inputs = Vec::new();
+            for pattern in &patterns {
+                inputs.extend(batch::expand(pattern).unwrap_or_else(|e| e.exit()));
+            }
+            let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+
+            let outcomes = batch::solve(day, &inputs, &day.parts(part), jobs);
+            if !batch(&outcomes, format) {
+                std::process::exit(1);
+            }
+        },
+        Opts::New { day, title, root } => {
+            let title = title.unwrap_or_else(|| format!("Day {}", day));
+            for path in scaffold::create(&root, day, &title).unwrap_or_else(|e| e.exit()) {
+                println!("Wrote {}", path.display());
+            }
+        },
+        Opts::Verify { day, answers } => {
+            let mut expected = answers::load(&answers).unwrap_or_else(|e| e.exit());
+            let days = match day {
+                Some(number) => {
+                    expected.retain(|e| e.day == number);
+                    let index = solution::DAYS.iter().position(|d| d.number == number);
+                    index.map_or(&solution::DAYS[..0], |i| &solution::DAYS[i..i + 1])
+                },
+                None => &solution::DAYS[..]
+            };
+
+            if !verify(days, &expected) {
+                std::process::exit(1);
+            }
+        }
+    }
+}
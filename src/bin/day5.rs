@@ -1,25 +1,95 @@
 extern crate aoc;
+
 use std::io;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+use aoc::{inputs, solution, Error, Format, Parts};
+use aoc::intcode::{self, transcript::{self, Transcript}, Machine};
+
+/// Run the diagnostic program interactively, or answer a part of the puzzle
+#[derive(StructOpt,Debug)]
+struct Opts {
+    /// Path to problem input [default: <input-dir>/5.txt]
+    #[structopt(parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Directory of inputs named by day
+    #[structopt(long, env = "AOC_INPUT_DIR", default_value = inputs::DEFAULT_DIR, parse(from_os_str))]
+    input_dir: PathBuf,
+
+    /// Answer part 1, 2 or all, rather than running interactively
+    #[structopt(short, long, conflicts_with_all = &["record", "replay"])]
+    part: Option<Parts>,
+
+    /// Output format for `--part`: table or json
+    #[structopt(long, default_value = "table")]
+    format: Format,
+
+    /// Record the session's input and output to a transcript file
+    #[structopt(long, parse(from_os_str), conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Replay a transcript file, reporting the first output that differs
+    #[structopt(long, parse(from_os_str))]
+    replay: Option<PathBuf>,
+}
+
+fn read(path: &Path) -> String {
+    inputs::read(path).unwrap_or_else(|e| e.exit())
+}
+
 fn main()
 {
-    match aoc::input() {
+    let args = Opts::from_args();
+
+    let input = read(&inputs::resolve(args.input.clone(), &args.input_dir, 5));
 
-        Some((input, sub)) => {
+    if let Some(parts) = args.part {
+        let day = solution::day(5).expect("Day 5 isn't registered");
+        solution::print(day, &input, &day.parts(parts), args.format);
+    }
 
-            let mut program = aoc::intcode::parse_input(input);
+    let mut program = match intcode::parse_input(&input) {
+        Ok(p) => p,
+        Err(e) => Error::Parse(e.to_string()).exit()
+    };
 
-            match sub {
-                _ => {
-                    let stdin = io::stdin();
-                    let mut stdout = io::stdout();
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
 
-                    aoc::intcode::execute(&mut program, &mut stdin.lock(), &mut stdout);
-                    std::process::exit(0);
-                }
-            };
-        },
-        None => {
-            std::process::exit(1);
+    let result = if let Some(path) = &args.replay {
+        let recorded = match Transcript::parse(&read(path)) {
+            Ok(t) => t,
+            Err(e) => Error::Parse(format!("{}: {}", path.display(), e)).exit()
+        };
+
+        match transcript::replay(&mut Machine::new(program), &recorded) {
+            Ok(()) => {
+                println!("Replay matches {} entries", recorded.entries.len());
+                Ok(())
+            },
+            Err(divergence) => {
+                eprintln!("Replay diverged: {}", divergence);
+                std::process::exit(1);
+            }
         }
     }
+    else if let Some(path) = &args.record {
+        let mut recorded = Transcript::new();
+        let result = transcript::record(&mut Machine::new(program), &mut stdin.lock(), &mut stdout, &mut recorded);
+
+        // Keep what was recorded even if the program failed
+        if let Err(e) = std::fs::write(path, recorded.to_string()) {
+            Error::Io(path.clone(), e).exit();
+        }
+        result
+    }
+    else {
+        intcode::execute(&mut program, &mut stdin.lock(), &mut stdout)
+    };
+
+    if let Err(e) = result {
+        Error::NoSolution(e.to_string()).exit();
+    }
+    std::process::exit(0);
 }
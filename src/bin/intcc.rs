@@ -0,0 +1,61 @@
+extern crate aoc;
+
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Compile a program to comma-separated intcode
+#[derive(StructOpt,Debug)]
+struct Opts {
+    /// Path to program source
+    #[structopt(parse(from_os_str))]
+    source: PathBuf,
+
+    /// Run the peephole optimizer over the output
+    #[structopt(short = "O", long)]
+    optimize: bool,
+
+    /// Write a binary image, with variable names, instead of text
+    #[structopt(long, parse(from_os_str))]
+    image: Option<PathBuf>,
+}
+
+fn main()
+{
+    let args = Opts::from_args();
+
+    let source = match std::fs::read_to_string(&args.source) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{}: {}", args.source.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    match aoc::intcode::compiler::compile_image(&source) {
+        Ok(mut image) => {
+            if args.optimize {
+                image.words = aoc::intcode::optimize::optimize(&image.words);
+            }
+
+            match args.image {
+                Some(path) => {
+                    let saved = std::fs::File::create(&path)
+                        .and_then(|mut f| aoc::intcode::save(&image, &mut f));
+                    if let Err(e) = saved {
+                        eprintln!("{}: {}", path.display(), e);
+                        std::process::exit(1);
+                    }
+                },
+                None => {
+                    let text: Vec<String> = image.words.iter().map(i64::to_string).collect();
+                    println!("{}", text.join(","));
+                }
+            };
+            std::process::exit(0);
+        },
+        Err(e) => {
+            eprintln!("{}: {}", args.source.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
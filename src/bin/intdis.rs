@@ -0,0 +1,86 @@
+extern crate aoc;
+
+use std::path::PathBuf;
+use structopt::StructOpt;
+use aoc::intcode::{self, coverage::Coverage, disassemble, Event, Image, Machine};
+
+/// Disassemble an intcode program, as text or a binary image
+#[derive(StructOpt,Debug)]
+struct Opts {
+    /// Path to program
+    #[structopt(parse(from_os_str))]
+    program: PathBuf,
+
+    /// Run the program on comma-separated inputs and annotate the listing with
+    /// what ran. Repeat to combine the coverage of several runs.
+    #[structopt(long = "run", number_of_values = 1)]
+    runs: Vec<String>,
+}
+
+/// Give up on a run after this many instructions
+const STEP_LIMIT: u64 = 100_000_000;
+
+fn load(bytes: Vec<u8>) -> Result<Image, String> {
+    if intcode::is_image(&bytes) {
+        intcode::load(&mut bytes.as_slice()).map_err(|e| e.to_string())
+    }
+    else {
+        let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let program = intcode::parse_input(&text).map_err(|e| e.to_string())?;
+        Ok(Image::new(program))
+    }
+}
+
+/// Run `image` on `inputs`, describing how the run went
+fn run(image: &Image, inputs: &str, coverage: &mut Coverage) -> Result<String, String> {
+    let mut machine = Machine::new(image.words.clone()).starting_at(image.entry).with_limit(STEP_LIMIT)
+        .with_observer(Coverage::new());
+    for input in inputs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        machine.push_input(input.parse().map_err(|_| format!("Bad input `{}`", input))?);
+    }
+
+    let mut outputs = Vec::new();
+    let result = loop {
+        match machine.run() {
+            Ok(Event::Output(value)) => outputs.push(value.to_string()),
+            Ok(Event::Pause) => continue,
+            Ok(Event::Halt) => break String::from("halted"),
+            Ok(Event::Input) => break format!("ran out of input at {}", machine.pc()),
+            Err(e) => break e.to_string()
+        };
+    };
+
+    coverage.merge(machine.observer());
+    Ok(format!("{}, output {}", result, outputs.join(",")))
+}
+
+fn listing(args: &Opts, bytes: Vec<u8>) -> Result<String, String> {
+    let image = load(bytes)?;
+    if args.runs.is_empty() {
+        return Ok(disassemble::symbolic_listing(&image));
+    }
+
+    let mut coverage = Coverage::new();
+    let mut text = String::new();
+    for (i, inputs) in args.runs.iter().enumerate() {
+        text.push_str(&format!("# run {} ({}): {}\n", i + 1, inputs, run(&image, inputs, &mut coverage)?));
+    }
+    text.push_str(&coverage.report(&image.words));
+    Ok(text)
+}
+
+fn main()
+{
+    let args = Opts::from_args();
+
+    match std::fs::read(&args.program).map_err(|e| e.to_string()).and_then(|bytes| listing(&args, bytes)) {
+        Ok(listing) => {
+            print!("{}", listing);
+            std::process::exit(0);
+        },
+        Err(e) => {
+            eprintln!("{}: {}", args.program.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+extern crate aoc;
+
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::thread;
+use structopt::StructOpt;
+use aoc::intcode::{self, server::{Config, Mode, Server}, Image};
+
+/// Serve an intcode program on a local TCP port, a fresh machine per
+/// connection, until killed.
+#[derive(StructOpt,Debug)]
+struct Opts {
+    /// Path to program, as text or a binary image
+    #[structopt(parse(from_os_str))]
+    program: PathBuf,
+
+    /// Port to listen on; by default any free one
+    #[structopt(short, long, default_value = "0")]
+    port: u16,
+
+    /// Exchange ASCII text rather than one integer per line
+    #[structopt(long)]
+    ascii: bool,
+
+    /// Instructions each session may execute
+    #[structopt(long, default_value = "100000000")]
+    budget: u64,
+
+    /// Stop once open sessions end when `quit` is typed or stdin closes
+    #[structopt(long)]
+    stdin: bool,
+}
+
+fn load(bytes: Vec<u8>) -> Result<Image, String> {
+    if intcode::is_image(&bytes) {
+        intcode::load(&mut bytes.as_slice()).map_err(|e| e.to_string())
+    }
+    else {
+        let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+        let program = intcode::parse_input(&text).map_err(|e| e.to_string())?;
+        Ok(Image::new(program))
+    }
+}
+
+fn main()
+{
+    let args = Opts::from_args();
+
+    let image = match std::fs::read(&args.program).map_err(|e| e.to_string()).and_then(load) {
+        Ok(image) => image,
+        Err(e) => {
+            eprintln!("{}: {}", args.program.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    let mode = if args.ascii { Mode::Ascii } else { Mode::Integers };
+    let server = match Server::bind(("127.0.0.1", args.port), image, Config { mode, budget: args.budget }) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Can't listen on port {}: {}", args.port, e);
+            std::process::exit(1);
+        }
+    };
+    println!("Listening on {}", server.local_addr());
+
+    if args.stdin {
+        let stopper = server.stopper();
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            for line in stdin.lock().lines() {
+                match line {
+                    Ok(line) if line.trim() != "quit" => continue,
+                    _ => break
+                }
+            }
+            stopper.stop();
+        });
+    }
+
+    match server.run() {
+        Ok(sessions) => {
+            println!("Stopped after {} sessions", sessions);
+            std::process::exit(0);
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::solution::Solution;
+
+/// Fuel needed to launch `mass`
+pub fn required_fuel(mass: i32) -> i32 {
+    (mass / 3) - 2
+}
+
+/// Fuel needed to launch `mass`, and the fuel for that fuel, and so on
+pub fn required_fuel_cumulative(mass: i32) -> i32 {
+
+    let mut total_fuel = 0;
+
+    let mut fuel = required_fuel(mass);
+
+    while fuel > 0 {
+        total_fuel += fuel;
+        fuel = required_fuel(fuel);
+    }
+
+    total_fuel
+}
+
+pub fn fuel_sum(masses: &[i32]) -> i32 {
+    masses.iter().copied().map(required_fuel).sum()
+}
+
+pub fn fuel_sum_alt(masses: &[i32]) -> i32 {
+    masses.iter().copied().map(required_fuel_cumulative).sum()
+}
+
+/// One module mass per line
+pub fn parse(input: &str) -> Result<Vec<i32>, String> {
+    input.lines()
+        .map(|mass| mass.trim().parse::<i32>().map_err(|_| format!("Invalid mass: {}", mass)))
+        .collect()
+}
+
+/// The mass of each module
+pub struct Modules {
+    masses: Vec<i32>
+}
+
+impl Solution for Modules {
+    type Answer = i32;
+
+    fn parse(input: &str) -> Result<Modules, String> {
+        Ok(Modules { masses: parse(input)? })
+    }
+
+    fn part1(&self) -> Result<i32, String> {
+        Ok(fuel_sum(&self.masses))
+    }
+
+    fn part2(&self) -> Result<i32, String> {
+        Ok(fuel_sum_alt(&self.masses))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example1() {
+        assert_eq!(required_fuel(12), 2);
+    }
+    #[test]
+    fn example2() {
+        assert_eq!(required_fuel(14), 2);
+    }
+    #[test]
+    fn example3() {
+        assert_eq!(required_fuel(1969), 654);
+    }
+    #[test]
+    fn example4() {
+        assert_eq!(required_fuel(100756), 33583);
+    }
+    #[test]
+    fn example2a() {
+        assert_eq!(required_fuel_cumulative(14), 2);
+    }
+    #[test]
+    fn example3a() {
+        assert_eq!(required_fuel_cumulative(1969), 966);
+    }
+    #[test]
+    fn example4a() {
+        assert_eq!(required_fuel_cumulative(100756), 50346);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::intcode::{self, InputProvider, OutputCollector};
+use crate::solution::Solution;
+
+/// Run `program` with the given noun and verb, returning what's left at address 0
+pub fn run(program: &[i64], noun: i64, verb: i64) -> Result<i64, intcode::Error> {
+    let mut p = program.to_vec();
+    p[1] = noun;
+    p[2] = verb;
+
+    intcode::execute(&mut p, &mut InputProvider::new(vec![]), &mut OutputCollector::new())?;
+    Ok(p[0])
+}
+
+/// The value left at address 0 after restoring the 1202 alarm state
+pub fn solve(program: &[i64]) -> Result<i64, intcode::Error> {
+    run(program, 12, 2)
+}
+
+/// `100 * noun + verb` for the inputs that produce 19690720
+pub fn solve_alt(program: &[i64]) -> Option<i64> {
+    for noun in 1..100 {
+        for verb in 1..100 {
+            if let Ok(19690720) = run(program, noun, verb) {
+                return Some((100 * noun) + verb);
+            }
+        }
+    }
+
+    None
+}
+
+/// The gravity assist program, which must have room for a noun and verb
+pub fn parse(input: &str) -> Result<Vec<i64>, String> {
+    let program = intcode::parse_input(input).map_err(|e| e.to_string())?;
+    if program.len() < 3 {
+        return Err(String::from("Program too short to take a noun and verb"));
+    }
+    Ok(program)
+}
+
+/// The gravity assist program
+pub struct Alarm {
+    program: Vec<i64>
+}
+
+impl Solution for Alarm {
+    type Answer = i64;
+
+    fn parse(input: &str) -> Result<Alarm, String> {
+        Ok(Alarm { program: parse(input)? })
+    }
+
+    fn part1(&self) -> Result<i64, String> {
+        solve(&self.program).map_err(|e| e.to_string())
+    }
+
+    fn part2(&self) -> Result<i64, String> {
+        solve_alt(&self.program).ok_or_else(|| String::from("No answer found"))
+    }
+}
@@ -0,0 +1,343 @@
+use std::str::FromStr;
+
+use crate::solution::Solution;
+
+#[derive(Debug,PartialEq, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+}
+
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "({},{})", self.x, self.y)
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+/// One straight run of wire
+pub struct PathComponent {
+    pub origin: Point,
+    pub direction: Direction,
+    pub distance: i32
+}
+
+impl PathComponent {
+    pub fn new(origin: Point, direction: Direction, distance: i32) -> PathComponent {
+        PathComponent { origin, direction, distance }
+    }
+
+    fn from_str(origin: Point, mut input: String) -> Result<PathComponent, String> {
+        let dir = match input.chars().next() {
+            Some('U') => Ok(Direction::Up),
+            Some('D') => Ok(Direction::Down),
+            Some('L') => Ok(Direction::Left),
+            Some('R') => Ok(Direction::Right),
+            Some(c) => Err(format!("Invalid direction: {}", c)),
+            None => Err(String::from("Cannot parse zero-length direction string"))
+        };
+
+        match dir {
+            Err(s) => Err(s),
+            Ok(d) => {
+                input.remove(0);
+                match input.parse::<i32>() {
+                    Ok(m) => Ok(PathComponent::new(origin, d,m)),
+                    Err(_) => Err(format!("Invalid magnitude: {}", input))
+                }
+            }
+        }
+    }
+
+    pub fn end(&self) -> Point {
+        match self.direction {
+            Direction::Up => Point::new(self.origin.x, self.origin.y + self.distance),
+            Direction::Down => Point::new(self.origin.x, self.origin.y - self.distance),
+            Direction::Right => Point::new(self.origin.x + self.distance, self.origin.y),
+            Direction::Left => Point::new(self.origin.x - self.distance, self.origin.y),
+        }
+    }
+
+    pub fn start(&self) -> Point {
+        self.origin
+    }
+
+    fn min_x(&self) -> i32 {
+        match self.direction {
+            Direction::Up | Direction::Down | Direction::Right => self.start().x,
+            Direction::Left => self.end().x,
+        }
+    }
+
+    fn max_x(&self) -> i32 {
+        match self.direction {
+            Direction::Up | Direction::Down | Direction::Left => self.start().x,
+            Direction::Right => self.end().x
+        }
+    }
+
+    fn min_y(&self) -> i32 {
+        match self.direction {
+            Direction::Left | Direction::Right | Direction::Up  => self.start().y,
+            Direction::Down => self.end().y
+        }
+    }
+
+    fn max_y(&self) -> i32 {
+        match self.direction {
+            Direction::Left | Direction::Right | Direction::Down=> self.start().y,
+            Direction::Up => self.end().y
+        }
+    }
+}
+
+/// Where two wires cross, and the segment of each that crosses there
+pub struct Collision {
+    pub first_segment: PathComponent,
+    pub second_segment: PathComponent,
+    pub location: Point
+}
+
+/// Where `a` and `b` cross, if they do
+pub fn collision(a: &PathComponent, b: &PathComponent) -> Option<Point> {
+    type D = Direction;
+
+    match (a.direction, b.direction) {
+        (D::Left, D::Up) | (D::Right, D::Up) | (D::Left, D::Down) | (D::Right, D::Down) => {
+            let ay = a.start().y;
+            let bx = b.start().x;
+
+            if ay > b.min_y() && ay < b.max_y() && bx > a.min_x() && bx < a.max_x() {
+                Some(Point::new(bx,ay))
+            }
+            else {
+                None
+            }
+        },
+        (D::Up, D::Left) | (D::Down, D::Left) | (D::Up, D::Right) | (D::Down, D::Right) => {
+            let ax = a.start().x;
+            let by = b.start().y;
+
+            if ax > b.min_x() && ax < b.max_x() && by > a.min_y() && by < a.max_y() {
+                Some(Point::new(ax,by))
+            }
+            else {
+                None
+            }
+        },
+        _ => None
+    }
+}
+
+impl Collision {
+    pub fn detect(a: &PathComponent, b: &PathComponent) -> Option<Collision> {
+        collision(a,b).map(|p| Collision {
+            first_segment: *a,
+            second_segment: *b,
+            location: p
+        })
+    }
+}
+
+
+pub fn taxicab_distance(a: &Point, b: &Point) -> i32 {
+    (a.x - b.x).abs() + (a.y - b.y).abs()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A wire, as segments laid end to end from the origin
+pub struct Path {
+    pub segments: Vec<PathComponent>
+}
+
+impl FromStr for Path {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Path, String> {
+        let mut origin = Point::new(0,0);
+
+        // Create a path component, updating the origin as a side-effect.
+        // The origin isn't updated if string parsing fails
+        let build_component = | s: String | {
+            let c = PathComponent::from_str(origin, s);
+            if let Ok(p) = &c {
+                origin = p.end();
+            }
+            c
+        };
+
+        let segments = 
+            input.trim().split(',')
+                 .map(String::from)
+                 .map(build_component)
+                 .collect();
+        
+        match segments {
+            Ok(segments) => Ok(Path {segments}),
+            Err(s) => Err(s)
+        }
+    }
+}
+
+/// Every crossing of `a` and `b`
+pub fn collisions(a: &Path, b: &Path) -> Vec<Collision> {
+
+    let mut collisions: Vec<Collision> = Vec::new();
+
+    for a_seg in &a.segments {
+        for b_seg in &b.segments {
+            if let Some(p) = Collision::detect(a_seg, b_seg) {
+                collisions.push(p);
+            }
+        }
+    }
+
+    collisions
+}
+
+/// The two wires, one per line
+pub fn parse(input: &str) -> Result<(Path,Path), String> {
+
+    let mut lines = input.lines();
+
+    match lines.next() {
+        Some(l1) => {
+            match lines.next() {
+                Some(l2) => {
+                    match (Path::from_str(l1), Path::from_str(l2)) {
+                        (Ok(p1), Ok(p2)) => Ok((p1, p2)),
+                        (Err(s), _) | (_, Err(s)) => Err(s)
+                    }
+                },
+                None => Err(String::from("Second input line not provided."))
+            }
+        },
+        None => Err(String::from("First input line not provided."))
+    }
+}
+
+/// Distance from the origin to the nearest crossing
+pub fn closest_collision(collisions: &[Collision]) -> Option<i32> {
+    let origin = Point::new(0,0);
+    collisions.iter().map(|c| taxicab_distance(&origin, &c.location)).min()
+}
+
+/// Wire length along `path` to `position` on `segment`
+pub fn steps_to(path: &Path, segment: &PathComponent, position: &Point) -> Option<i32> {
+
+    let mut steps = 0;
+
+    let reached = path.segments
+                      .iter()
+                      .any(|s| {
+                          if s != segment {
+                              steps += s.distance;
+                              false
+                          }
+                          else {
+                              steps += match s.direction {
+                                  Direction::Up | Direction::Down => {
+                                      (position.y - s.start().y).abs()
+                                  },
+                                  Direction::Left | Direction::Right => {
+                                      (position.x - s.start().x).abs()
+                                  }
+                              };
+                              true
+                          }
+                        });
+
+    if reached { Some(steps) } else { None }
+}
+
+/// Fewest combined steps along both wires to a crossing
+pub fn minimum_steps_to_collision(a: &Path, b: &Path) -> Option<i32> {
+
+    let collisions = collisions(a, b);
+
+    collisions.iter()
+              .filter_map(|c| { 
+                  let a_steps = steps_to(a, &c.first_segment, &c.location);
+                  let b_steps = steps_to(b, &c.second_segment, &c.location);
+                  match (a_steps, b_steps) {
+                      (Some(sa), Some(sb)) => Some(sa + sb),
+                      _ => None
+                  }
+                })
+              .min()
+}
+
+/// The two wires' paths
+pub struct Wires {
+    a: Path,
+    b: Path
+}
+
+impl Solution for Wires {
+    type Answer = i32;
+
+    fn parse(input: &str) -> Result<Wires, String> {
+        let (a, b) = parse(input)?;
+        Ok(Wires { a, b })
+    }
+
+    fn part1(&self) -> Result<i32, String> {
+        let collisions = collisions(&self.a, &self.b);
+        closest_collision(&collisions).ok_or_else(|| String::from("Paths do not collide."))
+    }
+
+    fn part2(&self) -> Result<i32, String> {
+        minimum_steps_to_collision(&self.a, &self.b).ok_or_else(|| String::from("Paths do not collide."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn eg1() {
+        let a = Path::from_str("R75,D30,R83,U83,L12,D49,R71,U7,L72");
+        let b = Path::from_str("U62,R66,U55,R34,D71,R55,D58,R83");
+        let collisions = collisions(&a.unwrap(), &b.unwrap());
+        assert_eq!(closest_collision(&collisions).unwrap(), 159);
+    }
+    
+    #[test]
+    fn eg2() {
+        let a = Path::from_str("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51");
+        let b = Path::from_str("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7");
+        let collisions = collisions(&a.unwrap(), &b.unwrap());
+        assert_eq!(closest_collision(&collisions).unwrap(), 135);
+    }
+
+    #[test]
+    fn eg1a() {
+        let a = Path::from_str("R75,D30,R83,U83,L12,D49,R71,U7,L72");
+        let b = Path::from_str("U62,R66,U55,R34,D71,R55,D58,R83");
+        assert_eq!(minimum_steps_to_collision(&a.unwrap(), &b.unwrap()).unwrap(), 610);
+    }
+    
+    #[test]
+    fn eg2a() {
+        let a = Path::from_str("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51");
+        let b = Path::from_str("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7");
+        assert_eq!(minimum_steps_to_collision(&a.unwrap(), &b.unwrap()).unwrap(), 410);
+    }
+}
\ No newline at end of file
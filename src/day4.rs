@@ -0,0 +1,150 @@
+use crate::solution::Solution;
+
+/// The puzzle's `min-max` range
+pub fn parse(input: &str) -> Result<(u32,u32), String> {
+    let mut input = input.trim().split('-');
+
+    match input.next() {
+        Some(low) => {
+            match input.next() {
+                Some(high) =>{
+                    match (low.parse::<u32>(), high.parse::<u32>()) {
+                        (Ok(low), Ok(high)) => Ok((low, high)),
+                        (Err(_), _) => Err(format!("Failed to parse range min: {}", low)),
+                        (Ok(_), Err(_)) => Err(format!("Failed to parse range max: {}", high))
+                    }
+                },
+                None => Err(String::from("No range max provided"))
+            }
+        },
+        None => Err(String::from("No range min provided"))
+    }
+}
+
+/// Digits never decrease, and at least two adjacent ones match
+pub fn valid_password(mut password: u32) -> bool {
+
+    let mut has_double = false;
+
+    let mut last_digit = password % 10;
+    password /= 10;
+
+    while password != 0 {
+        let next_digit = password % 10;
+
+        if next_digit > last_digit {
+            return false;
+        }
+
+        if last_digit == next_digit {
+            has_double = true;
+        }
+
+        last_digit = next_digit;
+        password /= 10;
+    }
+
+    has_double
+}
+
+/// As `valid_password`, but some pair of matching digits isn't part of a larger group
+pub fn valid_alternate_password(mut password: u32) -> bool {
+
+    let mut has_double = false;
+    let mut consecutive_digits = 1;
+
+    let mut last_digit = password % 10;
+    password /= 10;
+
+    while password != 0 {
+
+        let next_digit = password % 10;
+
+        if next_digit > last_digit {
+            return false;
+        }
+
+        if !has_double {
+
+            if next_digit == last_digit {
+                consecutive_digits += 1;
+            }
+            else {
+                if consecutive_digits == 2 {
+                    has_double = true;
+                }
+                consecutive_digits = 1;
+            }
+        }
+
+        last_digit = next_digit;
+        password /= 10;
+
+    }
+
+    has_double || (consecutive_digits == 2)
+}
+
+pub fn valid_passwords(min: u32, max:u32) -> Vec<u32> {
+    (min..max+1).filter(|x| valid_password(*x)).collect()
+}
+
+pub fn valid_alternate_passwords(min: u32, max:u32) -> Vec<u32> {
+    (min..max+1).filter(|x| valid_alternate_password(*x)).collect()
+}
+
+/// The range of passwords to check
+pub struct Passwords {
+    min: u32,
+    max: u32
+}
+
+impl Solution for Passwords {
+    type Answer = usize;
+
+    fn parse(input: &str) -> Result<Passwords, String> {
+        let (min, max) = parse(input)?;
+        Ok(Passwords { min, max })
+    }
+
+    fn part1(&self) -> Result<usize, String> {
+        Ok(valid_passwords(self.min, self.max).len())
+    }
+
+    fn part2(&self) -> Result<usize, String> {
+        Ok(valid_alternate_passwords(self.min, self.max).len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn eg1() {
+        assert!(valid_password(111111));
+    }
+    #[test]
+    fn eg2() {
+        assert!(!valid_password(223450));
+    }
+    #[test]
+    fn eg3() {
+        assert!(!valid_password(123789));
+    }
+
+    #[test]
+    fn eg1a() {
+        assert!(valid_alternate_password(112233));
+    }
+
+    #[test]
+    fn eg2a() {
+        assert!(!valid_alternate_password(123444));
+    }
+
+    #[test]
+    fn eg3a() {
+        assert!(valid_alternate_password(111122));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+use crate::intcode::{self, InputProvider, OutputCollector};
+use crate::solution::Solution;
+
+/// Run the diagnostic for the system with ID `system`, returning its last output
+pub fn diagnose(program: &[i64], system: i64) -> Result<i64, String> {
+    let mut output = OutputCollector::new();
+    intcode::execute(&mut program.to_vec(), &mut InputProvider::new(vec![system]), &mut output)
+        .map_err(|e| e.to_string())?;
+
+    output.outputs.last().copied().ok_or_else(|| String::from("No diagnostic code output"))
+}
+
+pub fn parse(input: &str) -> Result<Vec<i64>, String> {
+    intcode::parse_input(input).map_err(|e| e.to_string())
+}
+
+/// The thermal environment supervision terminal's diagnostic program
+pub struct Diagnostic {
+    program: Vec<i64>
+}
+
+impl Solution for Diagnostic {
+    type Answer = i64;
+
+    fn parse(input: &str) -> Result<Diagnostic, String> {
+        Ok(Diagnostic { program: parse(input)? })
+    }
+
+    fn part1(&self) -> Result<i64, String> {
+        diagnose(&self.program, 1)
+    }
+
+    fn part2(&self) -> Result<i64, String> {
+        diagnose(&self.program, 5)
+    }
+}
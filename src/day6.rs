@@ -0,0 +1,153 @@
+use crate::solution::Solution;
+use std::str::FromStr;
+use ego_tree::{Tree,NodeMut,NodeRef};
+
+#[derive(Debug)]
+/// `satellite` orbits `primary`
+pub struct Orbit {
+    pub primary: String,
+    pub satellite: String
+}
+
+impl FromStr for Orbit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+
+        let mut bodies = s.split(')');
+
+        match bodies.next() {
+            Some(primary) => {
+                match bodies.next() {
+                    Some(satellite) => {
+                        Ok(
+                            Orbit {
+                                primary: String::from(primary),
+                                satellite: String::from(satellite)
+                            }
+                        )
+                    },
+                    None => {
+                        Err(format!("Cannot parse satellite from {}", s))
+                    }
+                }
+            },
+            None => Err(format!("Cannot parse primary from \"{}\"", s))
+        }
+    }
+}
+
+
+/// One `primary)satellite` orbit per line
+pub fn parse(input: &str) -> Result<Vec<Orbit>, String> {
+    input.lines().map(|s| s.trim()).map(Orbit::from_str).collect()
+}
+
+fn build_tree(orbits: &[Orbit], mut root: NodeMut<String> ) {
+    // `orbits` is sorted by orbit primary, so search for the start
+    // of the slice with the root as its primary. The binary search
+    // might return an index in the slice other than the first, so
+    // iterate backwards until a non-matching primary is found and
+    // return the index
+    let start = match orbits.binary_search_by(|o| o.primary.cmp(root.value())) {
+        Ok(mut index) => { // At least one orbit
+            let iter = orbits.iter().rev().skip(orbits.len() - index);
+            for v in iter {
+                if &v.primary == root.value() {
+                    index -= 1;
+                }
+                else {
+                    break;
+                }
+            }
+            index
+        },
+        Err(_) => { // No orbits. Nothing to do.
+            return;
+        }
+    };
+
+    assert_eq!(root.value(), &orbits[start].primary);
+    if start != 0 {
+        assert_ne!(root.value(), &orbits[start - 1].primary);
+
+    }
+
+    let iter = orbits.iter().skip(start);
+    for orbit in iter {
+        if &orbit.primary == root.value() {
+            let child = root.append(orbit.satellite.clone());
+            build_tree(orbits, child);
+        }
+    }
+}
+
+/// The tree of orbits rooted at the universal Center of Mass, `COM`
+pub fn create_orbit_tree(mut orbits: Vec<Orbit>) -> Tree<String> {
+    orbits.sort_by(|a,b| a.primary.cmp(&b.primary));
+
+    let mut tree = Tree::new(String::from("COM"));
+    build_tree(&orbits, tree.root_mut());
+
+    tree
+}
+
+fn do_orbit_count(root_orbit: NodeRef<String>, root_depth: usize) -> usize {
+    let mut count = root_depth;
+    for satellite in root_orbit.children() {
+        count += do_orbit_count(satellite, root_depth + 1);
+    }
+    count
+}
+
+/// Total direct and indirect orbits
+pub fn orbit_count(orbits: &Tree<String>) -> usize {
+    do_orbit_count(orbits.root(), 0)
+}
+
+/// The map of which bodies orbit which
+pub struct Orbits {
+    tree: Tree<String>
+}
+
+impl Solution for Orbits {
+    type Answer = usize;
+    const PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Orbits, String> {
+        Ok(Orbits { tree: create_orbit_tree(parse(input)?) })
+    }
+
+    fn part1(&self) -> Result<usize, String> {
+        Ok(orbit_count(&self.tree))
+    }
+
+    fn part2(&self) -> Result<usize, String> {
+        unreachable!("Part 2 isn't solved")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eg() {
+        let input = String::from(
+            "COM)B
+             B)C
+             C)D
+             D)E
+             E)F
+             B)G
+             G)H
+             D)I
+             E)J
+             J)K
+             K)L");
+        
+        let o = parse(&input);
+        let tree = create_orbit_tree(o.unwrap());
+        assert_eq!(orbit_count(&tree), 42);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::intcode;
+use crate::solution::Solution;
+
+fn generate_permutations(k: usize, input: &mut Vec<i64>, output: &mut Vec<Vec<i64>>)
+{
+    match k {
+        1 => output.push(input.clone()),
+        k => {
+            generate_permutations(k - 1, input, output);
+
+            for i in 0..k-1 {
+
+                if k % 2 == 0 {
+                    input.swap(i, k-1);
+                }
+                else {
+                    input.swap(0, k-1);
+                }
+
+                generate_permutations(k-1, input, output);
+            }
+        }
+    };
+}
+
+/// Every ordering of the phase settings `0..stages`
+pub fn phase_permutations(stages: usize) -> Vec<Vec<i64>>
+{
+    let mut seed: Vec<i64> = (0..stages as i64).collect();
+    let mut out: Vec<Vec<i64>> = Vec::new();
+
+    generate_permutations(stages, &mut seed, &mut out);
+
+    out
+}
+
+
+/// The highest signal any ordering of phases gets from the amplifiers. It's
+/// an error for an amplifier to output nothing.
+pub fn max_signal(program: &[i64]) -> Result<i64, String> {
+    let mut max_output: i64 = 0;
+
+    for phases in phase_permutations(5) {
+
+        let mut stage_input = 0;
+
+        for phase in phases {
+            let mut program = program.to_vec();
+            let mut input = intcode::InputProvider::new(vec![phase,stage_input]);
+            let mut output = intcode::OutputCollector::new();
+            intcode::execute(&mut program, &mut input, &mut output).map_err(|e| e.to_string())?;
+
+            stage_input = *output.outputs.last()
+                .ok_or_else(|| format!("No output from the amplifier with phase {}", phase))?;
+        }
+
+        max_output = std::cmp::max(max_output, stage_input);
+    }
+
+    Ok(max_output)
+}
+
+pub fn parse(input: &str) -> Result<Vec<i64>, String> {
+    intcode::parse_input(input).map_err(|e| e.to_string())
+}
+
+/// The amplifier controller software
+pub struct Amplifiers {
+    program: Vec<i64>
+}
+
+impl Solution for Amplifiers {
+    type Answer = i64;
+    const PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Amplifiers, String> {
+        Ok(Amplifiers { program: parse(input)? })
+    }
+
+    fn part1(&self) -> Result<i64, String> {
+        max_signal(&self.program)
+    }
+
+    fn part2(&self) -> Result<i64, String> {
+        unreachable!("Part 2 isn't solved")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    extern crate factorial;
+    extern crate itertools;
+    
+    use factorial::Factorial;
+    use itertools::Itertools;
+
+    #[test]
+    fn permutations() {
+        for n in 1..5 {
+            let perms = phase_permutations(n);
+            let len = perms.len();
+            assert_eq!(len, n.factorial());
+            assert_eq!(perms.into_iter()
+                         .unique()
+                         .collect::<Vec<Vec<i64>>>()
+                         .len(),
+                        len);
+        }
+    }
+}
\ No newline at end of file
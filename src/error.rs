@@ -0,0 +1,89 @@
+//! Why a day's binary couldn't print an answer.
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::SubProblem;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input file couldn't be read
+    Io(PathBuf, io::Error),
+    /// The input isn't a valid puzzle input
+    Parse(String),
+    /// The input is valid, but no answer came of it
+    NoSolution(String),
+    /// There's no solver for this part yet
+    NotImplemented(SubProblem)
+}
+
+impl Error {
+    /// The process exit code for this error. 1 is left for usage errors
+    /// and failed checks.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(..) => 2,
+            Error::Parse(_) => 3,
+            Error::NoSolution(_) => 4,
+            Error::NotImplemented(_) => 5
+        }
+    }
+
+    /// Report the error on stderr and exit with its code
+    pub fn exit(&self) -> ! {
+        eprintln!("{}", self);
+        std::process::exit(self.exit_code())
+    }
+}
+
+// `io::Error` can't be cloned, so a copy keeps only its kind and message
+impl Clone for Error {
+    fn clone(&self) -> Error {
+        match self {
+            Error::Io(path, e) => Error::Io(path.clone(), io::Error::new(e.kind(), e.to_string())),
+            Error::Parse(s) => Error::Parse(s.clone()),
+            Error::NoSolution(s) => Error::NoSolution(s.clone()),
+            Error::NotImplemented(part) => Error::NotImplemented(*part)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Error::Parse(s) => write!(f, "Invalid input: {}", s),
+            Error::NoSolution(s) => write!(f, "No solution: {}", s),
+            Error::NotImplemented(part) => write!(f, "Part {} is not implemented", part)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(_, e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_differ() {
+        let errors = [
+            Error::Io(PathBuf::from("missing.txt"), io::Error::from(io::ErrorKind::NotFound)),
+            Error::Parse(String::from("Invalid mass: x")),
+            Error::NoSolution(String::from("Paths do not collide.")),
+            Error::NotImplemented(SubProblem::Two)
+        ];
+        let codes: Vec<i32> = errors.iter().map(Error::exit_code).collect();
+        assert_eq!(codes, vec![2, 3, 4, 5]);
+        assert_eq!(errors[1].to_string(), "Invalid input: Invalid mass: x");
+        assert_eq!(errors[0].clone().to_string(), errors[0].to_string());
+    }
+}
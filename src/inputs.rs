@@ -0,0 +1,88 @@
+//! Finding and reading a day's puzzle input.
+//!
+//! A path of `-` reads stdin. With no path, the input is `<day>.txt` in the
+//! input directory, which is `input` unless `--input-dir` or `AOC_INPUT_DIR`
+//! says otherwise.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+/// The directory inputs are kept in when none is given
+pub const DEFAULT_DIR: &str = "input";
+
+/// Where day `day`'s input is: `path` if given, else its file in `dir`
+pub fn resolve(path: Option<PathBuf>, dir: &Path, day: u32) -> PathBuf {
+    path.unwrap_or_else(|| dir.join(format!("{}.txt", day)))
+}
+
+/// Read the input at `path`, or stdin for `-`, normalised
+pub fn read(path: &Path) -> Result<String, Error> {
+    let mut text = String::new();
+    let result = if path == Path::new("-") {
+        std::io::stdin().read_to_string(&mut text).map(|_| ())
+    }
+    else {
+        std::fs::read_to_string(path).map(|s| text = s)
+    };
+
+    match result {
+        Ok(()) => Ok(normalise(&text)),
+        Err(e) => Err(Error::Io(path.to_path_buf(), e))
+    }
+}
+
+/// Strip a byte order mark and convert CRLF line endings
+pub fn normalise(text: &str) -> String {
+    text.trim_start_matches('\u{feff}').replace("\r\n", "\n")
+}
+
+/// Drop whitespace at the end of each line and blank lines at the end,
+/// keeping a final newline if there was one
+pub fn trim_trailing(text: &str) -> String {
+    let mut trimmed = text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let end = trimmed.trim_end().len();
+    trimmed.truncate(end);
+
+    if text.ends_with('\n') && !trimmed.is_empty() {
+        trimmed.push('\n');
+    }
+    trimmed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_path() {
+        assert_eq!(resolve(None, Path::new(DEFAULT_DIR), 4), PathBuf::from("input/4.txt"));
+        assert_eq!(resolve(None, Path::new("/tmp/aoc"), 12), PathBuf::from("/tmp/aoc/12.txt"));
+        assert_eq!(resolve(Some(PathBuf::from("-")), Path::new(DEFAULT_DIR), 4), PathBuf::from("-"));
+    }
+
+    #[test]
+    fn normalising() {
+        assert_eq!(normalise("\u{feff}R8,U5\r\nU7,R6\r\n"), "R8,U5\nU7,R6\n");
+        assert_eq!(normalise("12\n"), "12\n");
+    }
+
+    #[test]
+    fn trimming() {
+        assert_eq!(trim_trailing("12 \n14\t\n\n\n"), "12\n14\n");
+        assert_eq!(trim_trailing("1,2,99  "), "1,2,99");
+        assert_eq!(trim_trailing("  \n"), "");
+    }
+
+    #[test]
+    fn missing_file() {
+        match read(Path::new("input/does-not-exist.txt")) {
+            Err(Error::Io(path, _)) => assert_eq!(path, PathBuf::from("input/does-not-exist.txt")),
+            other => panic!("{:?}", other)
+        }
+    }
+}
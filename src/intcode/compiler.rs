@@ -0,0 +1,602 @@
+//! A compiler for a tiny imperative language targeting intcode.
+//!
+//! ```text
+//! # Sum the numbers from 1 to n
+//! n = input;
+//! total = 0;
+//! while n > 0 {
+//!     total = total + n;
+//!     n = n - 1;
+//! }
+//! output total;
+//! ```
+//!
+//! Values are `i64`. Expressions support literals, variables, `input`,
+//! `+ - *`, comparisons, `== !=`, `!`, `&&` and `||`, and parentheses.
+//! Both operands of `&&` and `||` are always evaluated. Statements are
+//! assignments, `output <expr>;`, `if`/`else` and `while`. A variable must
+//! be assigned before it is read.
+//!
+//! Code is laid out first, followed by one cell per variable and one per
+//! expression temporary.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::Image;
+
+#[derive(Debug, PartialEq)]
+pub struct CompileError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    If,
+    Else,
+    While,
+    Input,
+    Output,
+    Symbol(&'static str),
+    End
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::If => write!(f, "if"),
+            Token::Else => write!(f, "else"),
+            Token::While => write!(f, "while"),
+            Token::Input => write!(f, "input"),
+            Token::Output => write!(f, "output"),
+            Token::Symbol(s) => write!(f, "{}", s),
+            Token::End => write!(f, "end of input")
+        }
+    }
+}
+
+/// Longest symbols first, so `==` wins over `=`
+const SYMBOLS: [&str; 18] = [
+    "==", "!=", "<=", ">=", "&&", "||",
+    "=", "<", ">", "+", "-", "*", "!", "(", ")", "{", "}", ";"
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize, usize)>> {
+    let mut tokens = Vec::new();
+
+    for (l, text) in source.lines().enumerate() {
+        let line = l + 1;
+        let text = match text.find('#') {
+            Some(i) => &text[..i],
+            None => text
+        };
+
+        let mut rest = text;
+        while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[start..];
+            let column = text.len() - rest.len() + 1;
+            let c = rest.chars().next().unwrap();
+
+            let (token, length) = if c.is_ascii_digit() {
+                let length = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                match rest[..length].parse::<i64>() {
+                    Ok(n) => (Token::Number(n), length),
+                    Err(_) => return Err(CompileError {
+                        line, column, message: format!("Number out of range: {}", &rest[..length])
+                    })
+                }
+            }
+            else if c.is_alphabetic() || c == '_' {
+                let length = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+                let token = match &rest[..length] {
+                    "if" => Token::If,
+                    "else" => Token::Else,
+                    "while" => Token::While,
+                    "input" => Token::Input,
+                    "output" => Token::Output,
+                    word => Token::Ident(String::from(word))
+                };
+                (token, length)
+            }
+            else {
+                match SYMBOLS.iter().find(|s| rest.starts_with(*s)) {
+                    Some(s) => (Token::Symbol(s), s.len()),
+                    None => return Err(CompileError {
+                        line, column, message: format!("Unexpected character: {}", c)
+                    })
+                }
+            };
+
+            tokens.push((token, line, column));
+            rest = &rest[length..];
+        }
+    }
+
+    let line = source.lines().count().max(1);
+    tokens.push((Token::End, line, 1));
+
+    Ok(tokens)
+}
+
+/// A parameter before memory layout is known
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Immediate(i64),
+    Var(usize),
+    Temp(usize),
+    Label(usize)
+}
+
+struct Compiler {
+    tokens: Vec<(Token, usize, usize)>,
+    position: usize,
+    code: Vec<(i64, Vec<Operand>)>,
+    labels: Vec<usize>,
+    vars: HashMap<String, usize>,
+    temps: usize,
+    max_temps: usize
+}
+
+type Result<T> = std::result::Result<T, CompileError>;
+
+impl Compiler {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.position].0.clone();
+        if token != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T> {
+        let (_, line, column) = self.tokens[self.position];
+        Err(CompileError { line, column, message })
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<()> {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            Ok(())
+        }
+        else {
+            self.error(format!("Expected '{}', found '{}'", symbol, self.peek()))
+        }
+    }
+
+    fn accept(&mut self, symbol: &'static str) -> bool {
+        if *self.peek() == Token::Symbol(symbol) {
+            self.next();
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn emit(&mut self, opcode: i64, operands: Vec<Operand>) {
+        self.code.push((opcode, operands));
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(0);
+        self.labels.len() - 1
+    }
+
+    /// Place a label at the next emitted instruction
+    fn bind(&mut self, label: usize) {
+        self.labels[label] = self.code.len();
+    }
+
+    fn temp(&mut self) -> Operand {
+        self.temps += 1;
+        self.max_temps = self.max_temps.max(self.temps);
+        Operand::Temp(self.temps - 1)
+    }
+
+    fn block(&mut self) -> Result<()> {
+        self.expect("{")?;
+        while !self.accept("}") {
+            if *self.peek() == Token::End {
+                return self.error(String::from("Expected '}', found end of input"));
+            }
+            self.statement()?;
+        }
+        Ok(())
+    }
+
+    fn statement(&mut self) -> Result<()> {
+        match self.next() {
+            Token::Output => {
+                let value = self.expression()?;
+                self.expect(";")?;
+                self.emit(4, vec![value]);
+            },
+            Token::If => {
+                let otherwise = self.label();
+                let end = self.label();
+
+                let condition = self.expression()?;
+                self.emit(6, vec![condition, Operand::Label(otherwise)]);
+                self.block()?;
+
+                if *self.peek() == Token::Else {
+                    self.next();
+                    self.emit(5, vec![Operand::Immediate(1), Operand::Label(end)]);
+                    self.bind(otherwise);
+                    if *self.peek() == Token::If {
+                        self.statement()?;
+                    }
+                    else {
+                        self.block()?;
+                    }
+                }
+                else {
+                    self.bind(otherwise);
+                }
+                self.bind(end);
+            },
+            Token::While => {
+                let top = self.label();
+                let end = self.label();
+
+                self.bind(top);
+                let condition = self.expression()?;
+                self.emit(6, vec![condition, Operand::Label(end)]);
+                self.block()?;
+                self.emit(5, vec![Operand::Immediate(1), Operand::Label(top)]);
+                self.bind(end);
+            },
+            Token::Ident(name) => {
+                self.expect("=")?;
+                let value = self.expression()?;
+                self.expect(";")?;
+
+                let count = self.vars.len();
+                let var = *self.vars.entry(name).or_insert(count);
+                self.emit(1, vec![value, Operand::Immediate(0), Operand::Var(var)]);
+            },
+            token => {
+                if token != Token::End {
+                    self.position -= 1;
+                }
+                return self.error(format!("Expected a statement, found '{}'", token));
+            }
+        };
+
+        // Temporaries only live for the duration of a statement
+        self.temps = 0;
+        Ok(())
+    }
+
+    fn expression(&mut self) -> Result<Operand> {
+        self.or()
+    }
+
+    /// Emit `opcode a b -> t` into a fresh temporary
+    fn binary(&mut self, opcode: i64, a: Operand, b: Operand) -> Operand {
+        let t = self.temp();
+        self.emit(opcode, vec![a, b, t]);
+        t
+    }
+
+    fn not(&mut self, a: Operand) -> Operand {
+        self.binary(8, a, Operand::Immediate(0))
+    }
+
+    fn or(&mut self) -> Result<Operand> {
+        let mut a = self.and()?;
+        while self.accept("||") {
+            let b = self.and()?;
+            // Both zero exactly when the product of their negations is one
+            let na = self.not(a);
+            let nb = self.not(b);
+            let both = self.binary(2, na, nb);
+            a = self.not(both);
+        }
+        Ok(a)
+    }
+
+    fn and(&mut self) -> Result<Operand> {
+        let mut a = self.equality()?;
+        while self.accept("&&") {
+            let b = self.equality()?;
+            let na = self.not(a);
+            let nb = self.not(b);
+            let either = self.binary(1, na, nb);
+            a = self.not(either);
+        }
+        Ok(a)
+    }
+
+    fn equality(&mut self) -> Result<Operand> {
+        let mut a = self.comparison()?;
+        loop {
+            if self.accept("==") {
+                let b = self.comparison()?;
+                a = self.binary(8, a, b);
+            }
+            else if self.accept("!=") {
+                let b = self.comparison()?;
+                let eq = self.binary(8, a, b);
+                a = self.not(eq);
+            }
+            else {
+                return Ok(a);
+            }
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Operand> {
+        let mut a = self.additive()?;
+        loop {
+            if self.accept("<") {
+                let b = self.additive()?;
+                a = self.binary(7, a, b);
+            }
+            else if self.accept(">") {
+                let b = self.additive()?;
+                a = self.binary(7, b, a);
+            }
+            else if self.accept("<=") {
+                let b = self.additive()?;
+                let gt = self.binary(7, b, a);
+                a = self.not(gt);
+            }
+            else if self.accept(">=") {
+                let b = self.additive()?;
+                let lt = self.binary(7, a, b);
+                a = self.not(lt);
+            }
+            else {
+                return Ok(a);
+            }
+        }
+    }
+
+    fn additive(&mut self) -> Result<Operand> {
+        let mut a = self.multiplicative()?;
+        loop {
+            if self.accept("+") {
+                let b = self.multiplicative()?;
+                a = self.binary(1, a, b);
+            }
+            else if self.accept("-") {
+                let b = self.multiplicative()?;
+                let nb = self.negate(b);
+                a = self.binary(1, a, nb);
+            }
+            else {
+                return Ok(a);
+            }
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Operand> {
+        let mut a = self.unary()?;
+        while self.accept("*") {
+            let b = self.unary()?;
+            a = self.binary(2, a, b);
+        }
+        Ok(a)
+    }
+
+    fn negate(&mut self, a: Operand) -> Operand {
+        match a {
+            Operand::Immediate(n) => Operand::Immediate(n.wrapping_neg()),
+            a => self.binary(2, a, Operand::Immediate(-1))
+        }
+    }
+
+    fn unary(&mut self) -> Result<Operand> {
+        if self.accept("-") {
+            let a = self.unary()?;
+            Ok(self.negate(a))
+        }
+        else if self.accept("!") {
+            let a = self.unary()?;
+            Ok(self.not(a))
+        }
+        else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Operand> {
+        match self.next() {
+            Token::Number(n) => Ok(Operand::Immediate(n)),
+            Token::Input => {
+                let t = self.temp();
+                self.emit(3, vec![t]);
+                Ok(t)
+            },
+            Token::Ident(name) => {
+                match self.vars.get(&name) {
+                    Some(v) => Ok(Operand::Var(*v)),
+                    None => {
+                        self.position -= 1;
+                        self.error(format!("Variable '{}' read before assignment", name))
+                    }
+                }
+            },
+            Token::Symbol("(") => {
+                let a = self.expression()?;
+                self.expect(")")?;
+                Ok(a)
+            },
+            token => {
+                if token != Token::End {
+                    self.position -= 1;
+                }
+                self.error(format!("Expected an expression, found '{}'", token))
+            }
+        }
+    }
+
+    /// Lay out code, variables and temporaries, and resolve every operand
+    fn assemble(self) -> Image {
+        let mut addresses = Vec::new();
+        let mut length = 0;
+        for (_, operands) in &self.code {
+            addresses.push(length);
+            length += 1 + operands.len();
+        }
+        // Labels bound after the last instruction point at the final halt
+        addresses.push(length);
+        length += 1;
+
+        let vars = length;
+        let temps = vars + self.vars.len();
+
+        let mut program = Vec::with_capacity(temps + self.max_temps);
+        for (opcode, operands) in &self.code {
+            let mut modes = 0;
+            for (i, operand) in operands.iter().enumerate() {
+                if let Operand::Immediate(_) | Operand::Label(_) = operand {
+                    modes += 10_i64.pow(i as u32);
+                }
+            }
+
+            program.push(modes * 100 + opcode);
+            program.extend(operands.iter().map(|o| match *o {
+                Operand::Immediate(n) => n,
+                Operand::Label(l) => addresses[self.labels[l]] as i64,
+                Operand::Var(v) => (vars + v) as i64,
+                Operand::Temp(t) => (temps + t) as i64
+            }));
+        }
+
+        program.push(99);
+        program.resize(temps + self.max_temps, 0);
+
+        let mut image = Image::new(program);
+        for (name, v) in self.vars {
+            image.symbols.insert(vars + v, name);
+        }
+        image
+    }
+}
+
+/// Compile `source` into an intcode program
+pub fn compile(source: &str) -> Result<Vec<i64>> {
+    Ok(compile_image(source)?.words)
+}
+
+/// Compile `source` into an image with each variable's cell named
+pub fn compile_image(source: &str) -> Result<Image> {
+    let mut compiler = Compiler {
+        tokens: tokenize(source)?,
+        position: 0,
+        code: Vec::new(),
+        labels: Vec::new(),
+        vars: HashMap::new(),
+        temps: 0,
+        max_temps: 0
+    };
+
+    while *compiler.peek() != Token::End {
+        compiler.statement()?;
+    }
+
+    Ok(compiler.assemble())
+}
+
+/// Compile `source` into comma-separated text that `parse_input` can load
+pub fn compile_to_text(source: &str) -> Result<String> {
+    let program = compile(source)?;
+    Ok(program.iter().map(i64::to_string).collect::<Vec<String>>().join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{execute, parse_input, InputProvider, OutputCollector};
+
+    fn run(source: &str, inputs: Vec<i64>) -> Vec<i64> {
+        let mut program = parse_input(&compile_to_text(source).unwrap()).unwrap();
+        let mut input = InputProvider::new(inputs);
+        let mut output = OutputCollector::new();
+        execute(&mut program, &mut input, &mut output).unwrap();
+        output.outputs
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(run("output 2 + 3 * 4; output (2 + 3) * 4; output 7 - 10;", vec![]),
+                   vec![14, 20, -3]);
+        assert_eq!(run("x = input; y = input; output x - y * -2;", vec![5, 3]), vec![11]);
+    }
+
+    #[test]
+    fn comparisons() {
+        let source = "a = input; b = input;
+                      output a < b; output a > b; output a <= b; output a >= b;
+                      output a == b; output a != b;";
+        assert_eq!(run(source, vec![1, 2]), vec![1, 0, 1, 0, 0, 1]);
+        assert_eq!(run(source, vec![2, 2]), vec![0, 0, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn logic() {
+        let source = "a = input; b = input; output a && b; output a || b; output !a;";
+        assert_eq!(run(source, vec![0, 0]), vec![0, 0, 1]);
+        assert_eq!(run(source, vec![3, 0]), vec![0, 1, 0]);
+        assert_eq!(run(source, vec![3, -1]), vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn control_flow() {
+        let source = "
+            # Factorial, then classify the input
+            n = input;
+            f = 1;
+            while n > 1 {
+                f = f * n;
+                n = n - 1;
+            }
+            output f;
+            if f > 100 { output 1; } else if f > 10 { output 2; } else { output 3; }";
+        assert_eq!(run(source, vec![5]), vec![120, 1]);
+        assert_eq!(run(source, vec![4]), vec![24, 2]);
+        assert_eq!(run(source, vec![0]), vec![1, 3]);
+    }
+
+    #[test]
+    fn optimizes() {
+        let program = compile("x = 2 * 3; if 1 { output x; } while 0 { output 1; }").unwrap();
+        let optimized = super::super::optimize::optimize_checked(&program, &[vec![]]).unwrap();
+        assert_ne!(program, optimized);
+    }
+
+    #[test]
+    fn symbols() {
+        let image = compile_image("count = 3; total = count * 2; output total;").unwrap();
+        let names: Vec<&str> = image.symbols.values().map(String::as_str).collect();
+        assert_eq!(names, vec!["count", "total"]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(compile("x = 1;\noutput y;").unwrap_err(),
+                   CompileError { line: 2, column: 8, message: String::from("Variable 'y' read before assignment") });
+        assert_eq!(compile("output 1").unwrap_err().message, "Expected ';', found 'end of input'");
+        assert_eq!(compile("while 1 { output 1;").unwrap_err().message, "Expected '}', found end of input");
+        assert_eq!(compile("x = 1 $ 2;").unwrap_err(),
+                   CompileError { line: 1, column: 7, message: String::from("Unexpected character: $") });
+    }
+}
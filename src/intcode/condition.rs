@@ -0,0 +1,370 @@
+//! Breakpoint conditions.
+//!
+//! A condition is an expression over the state of a machine, such as
+//! `pc == 42 && [100] > 5`. It's parsed once into a tree of closures, so
+//! checking it on every instruction costs no more than the arithmetic.
+//!
+//! ```text
+//! pc        address of the instruction about to execute
+//! rb        the relative base
+//! steps     instructions executed so far
+//! out       the last value output, 0 before there is one
+//! [e]       the memory cell at address e, 0 outside memory
+//! 42, -1    numbers
+//! ```
+//!
+//! Operators, loosest binding first, are `||`, `&&`, the comparisons `==
+//! != < <= > >=`, `+ -`, `* / %` and the prefix `- !`, with parentheses for
+//! grouping. Everything is an integer: comparisons give 1 or 0, anything but
+//! 0 counts as true, arithmetic wraps, and dividing by zero gives 0.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::observer::{Control, Observer, State};
+use super::opcode::Opcode;
+
+#[derive(Debug, PartialEq)]
+pub struct SyntaxError {
+    /// Byte offset of the offending text in the condition
+    pub offset: usize,
+    pub message: String
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "byte {}: {}", self.offset, self.message)
+    }
+}
+
+/// What a condition can see
+struct Scope<'a, 'b> {
+    state: &'a State<'b>,
+    output: i64
+}
+
+type Eval = Box<dyn Fn(&Scope) -> i64 + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Number(i64),
+    Name(&'a str),
+    Symbol(&'static str),
+    End
+}
+
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "`{}`", n),
+            Token::Name(name) => write!(f, "`{}`", name),
+            Token::Symbol(symbol) => write!(f, "`{}`", symbol),
+            Token::End => write!(f, "the end")
+        }
+    }
+}
+
+/// Longest first, so `<=` isn't read as `<` then `=`
+const SYMBOLS: [&str; 18] = [
+    "==", "!=", "<=", ">=", "&&", "||",
+    "<", ">", "+", "-", "*", "/", "%", "!", "(", ")", "[", "]"
+];
+
+fn tokens(source: &str) -> Result<Vec<(usize, Token<'_>)>, SyntaxError> {
+    let mut tokens = Vec::new();
+    let mut at = 0;
+
+    while at < source.len() {
+        let rest = &source[at..];
+        let c = rest.chars().next().unwrap();
+
+        if c.is_whitespace() {
+            at += c.len_utf8();
+            continue;
+        }
+
+        let length = if c.is_ascii_digit() {
+            let length = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let number = rest[..length].parse().map_err(|_| SyntaxError {
+                offset: at, message: format!("`{}` is too large", &rest[..length])
+            })?;
+            tokens.push((at, Token::Number(number)));
+            length
+        }
+        else if c.is_ascii_alphabetic() || c == '_' {
+            let length = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+            tokens.push((at, Token::Name(&rest[..length])));
+            length
+        }
+        else {
+            match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                Some(symbol) => {
+                    tokens.push((at, Token::Symbol(symbol)));
+                    symbol.len()
+                },
+                None => return Err(SyntaxError { offset: at, message: format!("Unexpected `{}`", c) })
+            }
+        };
+        at += length;
+    }
+
+    tokens.push((source.len(), Token::End));
+    Ok(tokens)
+}
+
+/// How tightly a binary operator binds, or `None` if it isn't one
+fn precedence(symbol: &str) -> Option<u8> {
+    match symbol {
+        "||" => Some(1),
+        "&&" => Some(2),
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => Some(3),
+        "+" | "-" => Some(4),
+        "*" | "/" | "%" => Some(5),
+        _ => None
+    }
+}
+
+fn binary(symbol: &str, a: Eval, b: Eval) -> Eval {
+    match symbol {
+        "||" => Box::new(move |s| (a(s) != 0 || b(s) != 0) as i64),
+        "&&" => Box::new(move |s| (a(s) != 0 && b(s) != 0) as i64),
+        "==" => Box::new(move |s| (a(s) == b(s)) as i64),
+        "!=" => Box::new(move |s| (a(s) != b(s)) as i64),
+        "<" => Box::new(move |s| (a(s) < b(s)) as i64),
+        "<=" => Box::new(move |s| (a(s) <= b(s)) as i64),
+        ">" => Box::new(move |s| (a(s) > b(s)) as i64),
+        ">=" => Box::new(move |s| (a(s) >= b(s)) as i64),
+        "+" => Box::new(move |s| a(s).wrapping_add(b(s))),
+        "-" => Box::new(move |s| a(s).wrapping_sub(b(s))),
+        "*" => Box::new(move |s| a(s).wrapping_mul(b(s))),
+        "/" => Box::new(move |s| a(s).checked_div(b(s)).unwrap_or(0)),
+        "%" => Box::new(move |s| a(s).checked_rem(b(s)).unwrap_or(0)),
+        _ => unreachable!("`{}` is not a binary operator", symbol)
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token<'a>)>,
+    at: usize
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Token<'a> {
+        self.tokens[self.at].1
+    }
+
+    fn next(&mut self) -> (usize, Token<'a>) {
+        let token = self.tokens[self.at];
+        if token.1 != Token::End {
+            self.at += 1;
+        }
+        token
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), SyntaxError> {
+        match self.next() {
+            (_, Token::Symbol(s)) if s == symbol => Ok(()),
+            (offset, token) => Err(SyntaxError { offset, message: format!("Expected `{}`, found {}", symbol, token) })
+        }
+    }
+
+    /// An expression whose operators all bind tighter than `loosest`
+    fn expression(&mut self, loosest: u8) -> Result<Eval, SyntaxError> {
+        let mut left = self.operand()?;
+
+        while let Token::Symbol(symbol) = self.peek() {
+            match precedence(symbol) {
+                Some(p) if p > loosest => {
+                    self.next();
+                    let right = self.expression(p)?;
+                    left = binary(symbol, left, right);
+                },
+                _ => break
+            }
+        }
+
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Eval, SyntaxError> {
+        let (offset, token) = self.next();
+        Ok(match token {
+            Token::Number(n) => Box::new(move |_| n),
+            Token::Name("pc") => Box::new(|s| s.state.pc as i64),
+            Token::Name("rb") => Box::new(|s| s.state.base),
+            Token::Name("steps") => Box::new(|s| s.state.steps as i64),
+            Token::Name("out") => Box::new(|s| s.output),
+            Token::Name(name) => return Err(SyntaxError { offset, message: format!("Unknown name `{}`", name) }),
+            Token::Symbol("(") => {
+                let inner = self.expression(0)?;
+                self.expect(")")?;
+                inner
+            },
+            Token::Symbol("[") => {
+                let address = self.expression(0)?;
+                self.expect("]")?;
+                Box::new(move |s| {
+                    let a = address(s);
+                    if a < 0 { 0 } else { s.state.memory.get(a as usize).copied().unwrap_or(0) }
+                })
+            },
+            Token::Symbol("-") => {
+                let operand = self.operand()?;
+                Box::new(move |s| operand(s).wrapping_neg())
+            },
+            Token::Symbol("!") => {
+                let operand = self.operand()?;
+                Box::new(move |s| (operand(s) == 0) as i64)
+            },
+            token => return Err(SyntaxError { offset, message: format!("Expected a value, found {}", token) })
+        })
+    }
+}
+
+/// A parsed condition
+pub struct Condition {
+    source: String,
+    eval: Eval
+}
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Condition({:?})", self.source)
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Condition, SyntaxError> {
+        let mut parser = Parser { tokens: tokens(source)?, at: 0 };
+        let eval = parser.expression(0)?;
+
+        match parser.next() {
+            (_, Token::End) => Ok(Condition { source: String::from(source.trim()), eval }),
+            (offset, token) => Err(SyntaxError { offset, message: format!("Expected an operator, found {}", token) })
+        }
+    }
+
+    /// The value of the expression, given the last value output
+    pub fn evaluate(&self, state: &State, output: Option<i64>) -> i64 {
+        (self.eval)(&Scope { state, output: output.unwrap_or(0) })
+    }
+
+    pub fn holds(&self, state: &State, output: Option<i64>) -> bool {
+        self.evaluate(state, output) != 0
+    }
+}
+
+/// An observer pausing the machine before any instruction where one of its
+/// conditions holds
+#[derive(Debug, Default)]
+pub struct Breakpoints {
+    conditions: Vec<Condition>,
+    output: Option<i64>,
+    hit: Option<usize>
+}
+
+impl Breakpoints {
+    pub fn new() -> Breakpoints {
+        Breakpoints::default()
+    }
+
+    /// Add a breakpoint, returning its index
+    pub fn add(&mut self, condition: Condition) -> usize {
+        self.conditions.push(condition);
+        self.conditions.len() - 1
+    }
+
+    pub fn remove(&mut self, index: usize) -> Condition {
+        self.hit = None;
+        self.conditions.remove(index)
+    }
+
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    /// The index of the breakpoint that last paused the machine
+    pub fn hit(&self) -> Option<usize> {
+        self.hit
+    }
+}
+
+impl Observer for Breakpoints {
+    fn before(&mut self, _: usize, _: &Opcode, state: &State) -> Control {
+        let output = self.output;
+        self.hit = self.conditions.iter().position(|c| c.holds(state, output));
+        if self.hit.is_some() { Control::Pause } else { Control::Continue }
+    }
+
+    fn output(&mut self, value: i64) -> Control {
+        self.output = Some(value);
+        Control::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Event, Machine};
+
+    fn evaluate(source: &str) -> i64 {
+        let memory = [5, 10, 15];
+        let state = State { pc: 2, base: -1, steps: 7, memory: &memory };
+        Condition::parse(source).unwrap().evaluate(&state, Some(99))
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(evaluate("pc == 2 && [1] > 5"), 1);
+        assert_eq!(evaluate("1 + 2 * 3 - -4"), 11);
+        assert_eq!(evaluate("(1 + 2) * 3 % 5"), 4);
+        assert_eq!(evaluate("[pc] + [rb + 1] + [3] + [-1]"), 20);
+        assert_eq!(evaluate("steps / 2 == 3 || 7 / 0"), 1);
+        assert_eq!(evaluate("!(out != 99) && 1 < 2 && 2 <= 2 && 3 >= 4"), 0);
+        assert_eq!(evaluate("  out  "), 99);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = |source| Condition::parse(source).unwrap_err().to_string();
+        assert_eq!(error("pc == "), "byte 6: Expected a value, found the end");
+        assert_eq!(error("pc = 3"), "byte 3: Unexpected `=`");
+        assert_eq!(error("[100 > 5"), "byte 8: Expected `]`, found the end");
+        assert_eq!(error("ip == 2"), "byte 0: Unknown name `ip`");
+        assert_eq!(error("pc 2"), "byte 3: Expected an operator, found `2`");
+        assert_eq!(error("99999999999999999999"), "byte 0: `99999999999999999999` is too large");
+    }
+
+    #[test]
+    fn breaks_inside_loop() {
+        // Counts [14] down from 5, outputting each value
+        let program = vec![1101,5,0,14, 4,14, 1001,14,-1,14, 1005,14,4, 99, 0];
+        let mut breakpoints = Breakpoints::new();
+        breakpoints.add(Condition::parse("pc == 6 && [14] == 3").unwrap());
+        breakpoints.add(Condition::parse("out == 1").unwrap());
+
+        let mut machine = Machine::new(program).with_observer(breakpoints);
+        let mut events = Vec::new();
+        loop {
+            match machine.run().unwrap() {
+                Event::Halt => break,
+                Event::Pause => events.push(format!("break {} at {}", machine.observer().hit().unwrap(), machine.pc())),
+                event => events.push(format!("{:?}", event))
+            }
+        }
+
+        assert_eq!(events, [
+            "Output(5)", "Output(4)", "Output(3)", "break 0 at 6",
+            "Output(2)", "Output(1)", "break 1 at 6", "break 1 at 10", "break 1 at 13"
+        ]);
+    }
+}
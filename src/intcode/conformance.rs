@@ -0,0 +1,309 @@
+//! Conformance tests for intcode backends.
+//!
+//! The table holds every example program published with days 2, 5, 7 and 9,
+//! plus edge cases for each addressing mode and jump form. Any backend, or
+//! any change to how instructions execute, should pass all of it.
+
+use std::io::{self, BufRead, Read, Write};
+use std::iter;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use super::{execute_limited, optimize, threaded, Error, InputProvider, OutputCollector};
+
+/// Input fed from a channel, one value per line, ending when it disconnects
+struct PipeIn {
+    values: Receiver<i64>,
+    buffer: Vec<u8>
+}
+
+impl Read for PipeIn {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = {
+            let available = self.fill_buf()?;
+            let n = available.len().min(buf.len());
+            buf[..n].copy_from_slice(&available[..n]);
+            n
+        };
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl BufRead for PipeIn {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.buffer.is_empty() {
+            if let Ok(value) = self.values.recv() {
+                self.buffer = format!("{}\n", value).into_bytes();
+            }
+        }
+        Ok(&self.buffer)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buffer.drain(..amt);
+    }
+}
+
+/// Output sent down a channel, remembering the last value sent
+struct PipeOut {
+    values: Sender<i64>,
+    last: Option<i64>
+}
+
+impl Write for PipeOut {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let value = std::str::from_utf8(buf).ok()
+            .and_then(|s| s.trim().parse().ok())
+            .ok_or_else(|| io::Error::other("Parse error"))?;
+
+        // A halted reader in a feedback loop just misses the final value
+        let _ = self.values.send(value);
+        self.last = Some(value);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+type Backend = fn(&mut Vec<i64>, &mut PipeIn, &mut PipeOut) -> Result<(), Error>;
+
+struct Case {
+    name: &'static str,
+    program: &'static [i64],
+    input: &'static [i64],
+    output: &'static [i64],
+    /// Final memory, where it's worth pinning down
+    memory: Option<&'static [i64]>
+}
+
+const EQUAL_8_POSITION: &[i64] = &[3,9,8,9,10,9,4,9,99,-1,8];
+const LESS_8_POSITION: &[i64] = &[3,9,7,9,10,9,4,9,99,-1,8];
+const EQUAL_8_IMMEDIATE: &[i64] = &[3,3,1108,-1,8,3,4,3,99];
+const LESS_8_IMMEDIATE: &[i64] = &[3,3,1107,-1,8,3,4,3,99];
+const JUMP_POSITION: &[i64] = &[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9];
+const JUMP_IMMEDIATE: &[i64] = &[3,3,1105,-1,9,1101,0,0,12,4,12,99,1];
+const COMPARE_8: &[i64] = &[3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,
+                            46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99];
+const QUINE: &[i64] = &[109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99];
+
+const CASES: &[Case] = &[
+    // Day 2
+    Case { name: "day 2 example", program: &[1,9,10,3,2,3,11,0,99,30,40,50], input: &[], output: &[],
+           memory: Some(&[3500,9,10,70,2,3,11,0,99,30,40,50]) },
+    Case { name: "day 2 add", program: &[1,0,0,0,99], input: &[], output: &[], memory: Some(&[2,0,0,0,99]) },
+    Case { name: "day 2 multiply", program: &[2,3,0,3,99], input: &[], output: &[], memory: Some(&[2,3,0,6,99]) },
+    Case { name: "day 2 multiply at end", program: &[2,4,4,5,99,0], input: &[], output: &[],
+           memory: Some(&[2,4,4,5,99,9801]) },
+    Case { name: "day 2 overwrite halt", program: &[1,1,1,4,99,5,6,0,99], input: &[], output: &[],
+           memory: Some(&[30,1,1,4,2,5,6,0,99]) },
+
+    // Day 5
+    Case { name: "day 5 echo", program: &[3,0,4,0,99], input: &[42], output: &[42], memory: Some(&[42,0,4,0,99]) },
+    Case { name: "day 5 modes", program: &[1002,4,3,4,33], input: &[], output: &[], memory: Some(&[1002,4,3,4,99]) },
+    Case { name: "day 5 negative", program: &[1101,100,-1,4,0], input: &[], output: &[],
+           memory: Some(&[1101,100,-1,4,99]) },
+    Case { name: "day 5 equal position, equal", program: EQUAL_8_POSITION, input: &[8], output: &[1],
+           memory: Some(&[3,9,8,9,10,9,4,9,99,1,8]) },
+    Case { name: "day 5 equal position, not equal", program: EQUAL_8_POSITION, input: &[7], output: &[0],
+           memory: Some(&[3,9,8,9,10,9,4,9,99,0,8]) },
+    Case { name: "day 5 less position, less", program: LESS_8_POSITION, input: &[5], output: &[1],
+           memory: Some(&[3,9,7,9,10,9,4,9,99,1,8]) },
+    Case { name: "day 5 less position, not less", program: LESS_8_POSITION, input: &[8], output: &[0],
+           memory: Some(&[3,9,7,9,10,9,4,9,99,0,8]) },
+    Case { name: "day 5 equal immediate, equal", program: EQUAL_8_IMMEDIATE, input: &[8], output: &[1],
+           memory: Some(&[3,3,1108,1,8,3,4,3,99]) },
+    Case { name: "day 5 equal immediate, not equal", program: EQUAL_8_IMMEDIATE, input: &[9], output: &[0],
+           memory: Some(&[3,3,1108,0,8,3,4,3,99]) },
+    Case { name: "day 5 less immediate, less", program: LESS_8_IMMEDIATE, input: &[3], output: &[1],
+           memory: Some(&[3,3,1107,1,8,3,4,3,99]) },
+    Case { name: "day 5 less immediate, not less", program: LESS_8_IMMEDIATE, input: &[10], output: &[0],
+           memory: Some(&[3,3,1107,0,8,3,4,3,99]) },
+    Case { name: "day 5 jump position, zero", program: JUMP_POSITION, input: &[0], output: &[0],
+           memory: Some(&[3,12,6,12,15,1,13,14,13,4,13,99,0,0,1,9]) },
+    Case { name: "day 5 jump position, non-zero", program: JUMP_POSITION, input: &[5], output: &[1],
+           memory: Some(&[3,12,6,12,15,1,13,14,13,4,13,99,5,1,1,9]) },
+    Case { name: "day 5 jump immediate, zero", program: JUMP_IMMEDIATE, input: &[0], output: &[0],
+           memory: Some(&[3,3,1105,0,9,1101,0,0,12,4,12,99,0]) },
+    Case { name: "day 5 jump immediate, non-zero", program: JUMP_IMMEDIATE, input: &[5], output: &[1],
+           memory: Some(&[3,3,1105,5,9,1101,0,0,12,4,12,99,1]) },
+    Case { name: "day 5 compare, below", program: COMPARE_8, input: &[7], output: &[999], memory: None },
+    Case { name: "day 5 compare, equal", program: COMPARE_8, input: &[8], output: &[1000], memory: None },
+    Case { name: "day 5 compare, above", program: COMPARE_8, input: &[9], output: &[1001], memory: None },
+
+    // Day 9
+    Case { name: "day 9 quine", program: QUINE, input: &[], output: QUINE, memory: None },
+    Case { name: "day 9 large product", program: &[1102,34915192,34915192,7,4,7,99,0], input: &[],
+           output: &[1219070632396864], memory: Some(&[1102,34915192,34915192,7,4,7,99,1219070632396864]) },
+    Case { name: "day 9 large immediate", program: &[104,1125899906842624,99], input: &[],
+           output: &[1125899906842624], memory: Some(&[104,1125899906842624,99]) },
+
+    // Addressing modes
+    Case { name: "position read", program: &[4,3,99,7], input: &[], output: &[7], memory: None },
+    Case { name: "immediate read", program: &[104,-7,99], input: &[], output: &[-7], memory: None },
+    Case { name: "relative read", program: &[109,5,204,1,99,0,42], input: &[], output: &[42], memory: None },
+    Case { name: "relative read, negative offset", program: &[109,10,204,-4,99,0,13], input: &[], output: &[13],
+           memory: None },
+    Case { name: "relative write", program: &[109,7,21101,2,3,0,99,0], input: &[], output: &[],
+           memory: Some(&[109,7,21101,2,3,0,99,5]) },
+    Case { name: "relative input", program: &[109,6,203,0,99,0,0], input: &[9], output: &[],
+           memory: Some(&[109,6,203,0,99,0,9]) },
+    Case { name: "relative base adjustments add up", program: &[109,4,209,4,204,0,99,55,3], input: &[],
+           output: &[55], memory: None },
+    Case { name: "negative values", program: &[1101,-3,-4,7,4,7,99,0], input: &[], output: &[-7],
+           memory: Some(&[1101,-3,-4,7,4,7,99,-7]) },
+    Case { name: "read past the end", program: &[4,100,99], input: &[], output: &[0], memory: Some(&[4,100,99]) },
+    Case { name: "write past the end", program: &[1101,1,2,6,99], input: &[], output: &[],
+           memory: Some(&[1101,1,2,6,99,0,3]) },
+
+    // Jumps: taken jumps output 1, falling through outputs 0
+    Case { name: "jnz immediate, taken", program: &[1105,1,6,104,0,99,104,1,99], input: &[], output: &[1], memory: None },
+    Case { name: "jnz immediate, not taken", program: &[1105,0,6,104,0,99,104,1,99], input: &[], output: &[0], memory: None },
+    Case { name: "jz immediate, taken", program: &[1106,0,6,104,0,99,104,1,99], input: &[], output: &[1], memory: None },
+    Case { name: "jz immediate, not taken", program: &[1106,3,6,104,0,99,104,1,99], input: &[], output: &[0], memory: None },
+    Case { name: "jnz position", program: &[5,9,10,104,0,99,104,1,99,-1,6], input: &[], output: &[1], memory: None },
+    Case { name: "jz position, not taken", program: &[6,9,10,104,0,99,104,1,99,-1,6], input: &[], output: &[0],
+           memory: None },
+    Case { name: "jz relative", program: &[109,11,2206,0,1,104,0,99,104,1,99,0,8], input: &[], output: &[1],
+           memory: None },
+    Case { name: "jump to the end halts", program: &[1105,1,3], input: &[], output: &[], memory: Some(&[1105,1,3]) },
+    Case { name: "running off the end halts", program: &[104,3], input: &[], output: &[3], memory: None }
+];
+
+struct Amplifiers {
+    name: &'static str,
+    program: &'static [i64],
+    phases: [i64; 5],
+    feedback: bool,
+    signal: i64
+}
+
+const AMPLIFIERS: &[Amplifiers] = &[
+    Amplifiers { name: "day 7 first example", program: &[3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0],
+                 phases: [4,3,2,1,0], feedback: false, signal: 43210 },
+    Amplifiers { name: "day 7 second example",
+                 program: &[3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0],
+                 phases: [0,1,2,3,4], feedback: false, signal: 54321 },
+    Amplifiers { name: "day 7 third example",
+                 program: &[3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,
+                            99,0,0,0],
+                 phases: [1,0,4,3,2], feedback: false, signal: 65210 },
+    Amplifiers { name: "day 7 feedback example",
+                 program: &[3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5],
+                 phases: [9,8,7,6,5], feedback: true, signal: 139629729 },
+    Amplifiers { name: "day 7 second feedback example",
+                 program: &[3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,
+                            53,1008,54,0,55,1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10],
+                 phases: [9,7,8,5,6], feedback: true, signal: 18216 }
+];
+
+/// Programs that stop without halting, with the error each stops with
+const ERRORS: &[(&str, &[i64], &str)] = &[
+    ("unknown opcode", &[50,0,0,0], "Unknown opcode 50 at 0"),
+    ("negative opcode", &[104,1,-1], "Unknown opcode -1 at 2"),
+    ("bad mode", &[301,0,0,0,99], "Bad addressing mode in 301 at 0"),
+    ("truncated", &[1,0,0], "Truncated instruction at 0"),
+    ("negative address", &[4,-1,99], "Bad address -1 at 0"),
+    ("relative address below zero", &[109,-5,204,0,99], "Bad address -5 at 2"),
+    ("address past the limit", &[1101,1,1,2000000,99], "Bad address 2000000 at 0"),
+    ("negative jump", &[1105,1,-3], "Bad address -3 at 0"),
+    ("input exhausted", &[3,0,99], "Input exhausted at 0"),
+    ("step limit", &[1105,1,0], "No halt after 100 steps")
+];
+
+/// Run a program, returning its outputs and final memory
+fn run(backend: Backend, program: &[i64], inputs: &[i64]) -> Result<(Vec<i64>, Vec<i64>), Error> {
+    let (sender, values) = channel();
+    for value in inputs {
+        sender.send(*value).unwrap();
+    }
+    drop(sender);
+
+    let (sender, outputs) = channel();
+    let mut memory = program.to_vec();
+    backend(&mut memory, &mut PipeIn { values, buffer: Vec::new() }, &mut PipeOut { values: sender, last: None })?;
+
+    Ok((outputs.try_iter().collect(), memory))
+}
+
+/// Run a chain of amplifiers, each on its own thread, returning the final signal
+fn amplify(backend: Backend, amplifiers: &Amplifiers) -> i64 {
+    let (senders, receivers): (Vec<Sender<i64>>, Vec<Receiver<i64>>) =
+        amplifiers.phases.iter().map(|_| channel()).unzip();
+
+    for (sender, phase) in senders.iter().zip(&amplifiers.phases) {
+        sender.send(*phase).unwrap();
+    }
+    senders[0].send(0).unwrap();
+
+    // The last amplifier feeds the first, or nothing
+    let last = if amplifiers.feedback { senders[0].clone() } else { channel().0 };
+
+    let threads: Vec<_> = receivers.into_iter()
+        .zip(senders.into_iter().skip(1).chain(iter::once(last)))
+        .map(|(values, sender)| {
+            let mut memory = amplifiers.program.to_vec();
+            thread::spawn(move || {
+                let mut output = PipeOut { values: sender, last: None };
+                backend(&mut memory, &mut PipeIn { values, buffer: Vec::new() }, &mut output).unwrap();
+                output.last
+            })
+        })
+        .collect();
+
+    let signals: Vec<Option<i64>> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    signals[signals.len() - 1].expect("No signal from the last amplifier")
+}
+
+fn conforms(backend: Backend, check_memory: bool) {
+    for case in CASES {
+        let (output, memory) = run(backend, case.program, case.input)
+            .unwrap_or_else(|e| panic!("{}: {}", case.name, e));
+        assert_eq!(output, case.output, "{}: output", case.name);
+        if let (true, Some(expected)) = (check_memory, case.memory) {
+            assert_eq!(memory, expected, "{}: memory", case.name);
+        }
+    }
+
+    for amplifiers in AMPLIFIERS {
+        assert_eq!(amplify(backend, amplifiers), amplifiers.signal, "{}", amplifiers.name);
+    }
+}
+
+#[test]
+fn interpreter() {
+    conforms(super::execute, true);
+}
+
+#[test]
+fn threaded() {
+    conforms(threaded::execute, true);
+}
+
+#[test]
+fn optimized() {
+    // Rewritten instructions change memory, but never what a program outputs
+    conforms(|program, input, output| super::execute(&mut optimize::optimize(program), input, output), false);
+}
+
+#[test]
+fn errors() {
+    type Limited = fn(&mut Vec<i64>, &mut InputProvider, &mut OutputCollector, u64) -> Result<u64, Error>;
+
+    for backend in &[execute_limited as Limited, threaded::execute_limited] {
+        for (name, program, expected) in ERRORS {
+            let mut memory = program.to_vec();
+            let result = backend(&mut memory, &mut InputProvider::new(vec![]), &mut OutputCollector::new(), 100);
+            assert_eq!(result.unwrap_err().to_string(), *expected, "{}", name);
+        }
+
+        let mut input = InputProvider { inputs: String::from("x\n") };
+        let result = backend(&mut vec![3,0,99], &mut input, &mut OutputCollector::new(), 100);
+        assert_eq!(result.unwrap_err().to_string(), "Bad input `x` at 0");
+
+        let steps = backend(&mut vec![104,1,99], &mut InputProvider::new(vec![]), &mut OutputCollector::new(), 2);
+        assert_eq!(steps.unwrap(), 2);
+    }
+}
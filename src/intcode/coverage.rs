@@ -0,0 +1,217 @@
+//! Execution coverage of intcode programs.
+//!
+//! `Coverage` observes a `Machine`, counting how often each address runs as
+//! an instruction, noting every address read or written as data, and
+//! counting which way each branch went. The report is a disassembly listing with
+//! those annotations, to show what a set of inputs actually exercises.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{disassemble, try_decode};
+use super::observer::{Control, Observer, State};
+use super::opcode::{self, Effect, Opcode, Role, Table};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Coverage {
+    /// Times each address ran as an instruction
+    pub executed: Vec<u64>,
+    pub read: Vec<bool>,
+    pub written: Vec<bool>,
+    /// Times the branch at each address jumped, and fell through
+    pub branches: BTreeMap<usize, (u64, u64)>
+}
+
+/// Set `map[address]`, growing `map` as needed
+fn mark<T: Clone + Default>(map: &mut Vec<T>, address: usize) -> &mut T {
+    if address >= map.len() {
+        map.resize(address + 1, T::default());
+    }
+    &mut map[address]
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    /// Add the coverage of another run of the same program
+    pub fn merge(&mut self, other: &Coverage) {
+        for (address, count) in other.executed.iter().enumerate() {
+            *mark(&mut self.executed, address) += count;
+        }
+        for address in other.read.iter().enumerate().filter(|(_, r)| **r).map(|(a, _)| a) {
+            *mark(&mut self.read, address) = true;
+        }
+        for address in other.written.iter().enumerate().filter(|(_, w)| **w).map(|(a, _)| a) {
+            *mark(&mut self.written, address) = true;
+        }
+        for (address, (jumped, fell)) in &other.branches {
+            let counts = self.branches.entry(*address).or_insert((0, 0));
+            counts.0 += jumped;
+            counts.1 += fell;
+        }
+    }
+
+    fn executions(&self, address: usize) -> u64 {
+        self.executed.get(address).copied().unwrap_or(0)
+    }
+
+    /// An annotated disassembly of `program`: how often each instruction
+    /// ran (`-` for never), which way branches went, and which words were
+    /// used as data
+    pub fn report(&self, program: &[i64]) -> String {
+        self.report_with_table(program, &opcode::table())
+    }
+
+    /// `report` for a program run with `table` rather than the registered
+    /// opcodes
+    pub fn report_with_table(&self, program: &[i64], table: &Table) -> String {
+        let lines = disassemble::disassemble_with_table(program, table);
+
+        let mut instructions = (0, 0);
+        let mut branches = (0, 0);
+        let mut text = String::new();
+
+        for line in &lines {
+            let instruction = try_decode(program, line.address, table);
+            let hits = self.executions(line.address);
+            let range = line.address..line.address + line.words.len();
+
+            let mut notes = Vec::new();
+            if let Some(instruction) = &instruction {
+                instructions.0 += 1;
+                if hits > 0 {
+                    instructions.1 += 1;
+                }
+
+                if instruction.opcode.params.contains(&Role::Jump) {
+                    let (jumped, fell) = self.branches.get(&line.address).copied().unwrap_or((0, 0));
+                    branches.0 += 1;
+                    if jumped > 0 && fell > 0 {
+                        branches.1 += 1;
+                    }
+                    if hits > 0 {
+                        notes.push(format!("jumped {}, fell through {}", jumped, fell));
+                    }
+                }
+            }
+            if range.clone().any(|a| self.read.get(a) == Some(&true)) {
+                notes.push(String::from("read"));
+            }
+            if range.clone().any(|a| self.written.get(a) == Some(&true)) {
+                notes.push(String::from("written"));
+            }
+
+            let hits = match (hits, &instruction) {
+                (0, Some(_)) => String::from("-"),
+                (0, None) => String::new(),
+                (n, _) => n.to_string()
+            };
+            let words: Vec<String> = line.words.iter().map(i64::to_string).collect();
+            let line = format!("{:>8}  {:>6}  {:<24} {:<28} {}", hits, line.address, words.join(","), line.text,
+                               notes.join(", "));
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+
+        format!("# {} of {} instructions executed, {} of {} branches went both ways\n{}",
+                instructions.1, instructions.0, branches.1, branches.0, text)
+    }
+}
+
+impl Observer for Coverage {
+    fn before(&mut self, pc: usize, _: &Opcode, _: &State) -> Control {
+        *mark(&mut self.executed, pc) += 1;
+        Control::Continue
+    }
+
+    fn after(&mut self, pc: usize, opcode: &Opcode, effect: Effect, _: &State) -> Control {
+        if opcode.params.contains(&Role::Jump) {
+            let counts = self.branches.entry(pc).or_insert((0, 0));
+            if let Effect::Jump(_) = effect { counts.0 += 1 } else { counts.1 += 1 }
+        }
+        Control::Continue
+    }
+
+    fn read(&mut self, address: usize, _: i64) -> Control {
+        *mark(&mut self.read, address) = true;
+        Control::Continue
+    }
+
+    fn write(&mut self, address: usize, _: i64) -> Control {
+        *mark(&mut self.written, address) = true;
+        Control::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Event, Machine};
+
+    fn run(program: &[i64], inputs: &[i64]) -> Coverage {
+        let mut machine = Machine::new(program.to_vec()).with_observer(Coverage::new());
+        for input in inputs {
+            machine.push_input(*input);
+        }
+        while machine.run().unwrap() != Event::Halt {}
+        machine.observer().clone()
+    }
+
+    #[test]
+    fn marks_addresses() {
+        // Reads 13, writes 14 and skips the output when the input is zero
+        let program = [3,13, 1006,13,8, 104,1,99, 1001,13,1,14, 99, 0, 0];
+        let coverage = run(&program, &[0]);
+
+        assert_eq!(coverage.executions(0), 1);
+        assert_eq!(coverage.executions(5), 0);
+        assert_eq!(coverage.branches[&2], (1, 0));
+        assert!(coverage.read[13] && coverage.written[13] && coverage.written[14]);
+        assert_eq!(coverage.read.get(14), None);
+    }
+
+    #[test]
+    fn merges_runs() {
+        let program = [3,13, 1006,13,8, 104,1,99, 1001,13,1,14, 99, 0, 0];
+        let mut coverage = run(&program, &[0]);
+        coverage.merge(&run(&program, &[4]));
+        assert_eq!(coverage.branches[&2], (1, 1));
+        assert_eq!(coverage.executions(0), 2);
+        assert_eq!(coverage.executions(5), 1);
+    }
+
+    #[test]
+    fn annotated_listing() {
+        let program = [3,9, 1005,9,7, 104,0, 99, 0, 0];
+        let report = run(&program, &[1]).report(&program);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "# 3 of 4 instructions executed, 0 of 1 branches went both ways");
+        assert!(lines[1].starts_with("       1       0  3,9"));
+        assert!(lines[2].ends_with("jnz [9], 7                   jumped 1, fell through 0"));
+        assert!(lines[3].starts_with("       -       5  104,0"));
+        assert!(lines[6].ends_with("data 0                       read, written"));
+    }
+
+    #[test]
+    fn listing_with_table() {
+        let mut table = Table::standard();
+        table.insert(Opcode { code: 42, mnemonic: "sq", params: &[Role::Read, Role::Write],
+                              semantics: |v| Effect::Store(v[0] * v[0]) }).unwrap();
+        let program = [142,3,5, 99, 0, 0];
+
+        let mut machine = Machine::new(program.to_vec()).with_table(table.clone()).with_observer(Coverage::new());
+        assert_eq!(machine.run().unwrap(), Event::Halt);
+        let report = machine.observer().report_with_table(&program, &table);
+        let lines: Vec<&str> = report.lines().collect();
+
+        assert_eq!(lines[0], "# 2 of 2 instructions executed, 0 of 0 branches went both ways");
+        assert!(lines[1].starts_with("       1       0  142,3,5"));
+        assert!(lines[1].contains("sq 3, [5]"));
+        assert!(lines[4].ends_with("written"));
+    }
+}
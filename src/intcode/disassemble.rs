@@ -0,0 +1,138 @@
+//! Disassembly of intcode program images.
+//!
+//! Disassembly is a linear sweep from address zero. Words that don't decode
+//! as an instruction with every opcode in the registry are listed as data, one
+//! word at a time.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{try_decode, AddressingMode, Image, Instruction};
+use super::opcode::{self, Role, Table};
+
+/// One instruction, or one word of data
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub address: usize,
+    pub words: Vec<i64>,
+    pub text: String
+}
+
+fn describe(instruction: &Instruction, symbols: &BTreeMap<usize, String>) -> String {
+    let address = |value: i64| {
+        match symbols.get(&(value as usize)) {
+            Some(name) if value >= 0 => name.clone(),
+            _ => value.to_string()
+        }
+    };
+
+    let params: Vec<String> = instruction.params.iter()
+        .zip(instruction.opcode.params)
+        .map(|(param, role)| match (role, &param.mode) {
+            (_, AddressingMode::Relative) if param.value < 0 => format!("[rb{}]", param.value),
+            (_, AddressingMode::Relative) => format!("[rb+{}]", param.value),
+            (Role::Write, _) | (_, AddressingMode::Position) => format!("[{}]", address(param.value)),
+            (Role::Jump, AddressingMode::Immediate) => address(param.value),
+            (Role::Read, AddressingMode::Immediate) => param.value.to_string()
+        })
+        .collect();
+
+    if params.is_empty() {
+        String::from(instruction.opcode.mnemonic)
+    }
+    else {
+        format!("{} {}", instruction.opcode.mnemonic, params.join(", "))
+    }
+}
+
+pub fn disassemble(program: &[i64]) -> Vec<Line> {
+    lines(program, &BTreeMap::new(), &opcode::table())
+}
+
+/// `disassemble` with `table` rather than the registered opcodes
+pub fn disassemble_with_table(program: &[i64], table: &Table) -> Vec<Line> {
+    lines(program, &BTreeMap::new(), table)
+}
+
+fn lines(program: &[i64], symbols: &BTreeMap<usize, String>, table: &Table) -> Vec<Line> {
+    let mut lines = Vec::new();
+
+    let mut address = 0;
+    while address < program.len() {
+        let (length, text) = match try_decode(program, address, table) {
+            Some(instruction) => (instruction.opcode.length(), describe(&instruction, symbols)),
+            None => (1, format!("data {}", program[address]))
+        };
+
+        lines.push(Line {
+            address,
+            words: program[address..address + length].to_vec(),
+            text
+        });
+        address += length;
+    }
+
+    lines
+}
+
+fn format(lines: &[Line], symbols: &BTreeMap<usize, String>) -> String {
+    let mut text = String::new();
+    for line in lines {
+        if let Some(name) = symbols.get(&line.address) {
+            text.push_str(&format!("{}:\n", name));
+        }
+        let words: Vec<String> = line.words.iter().map(i64::to_string).collect();
+        text.push_str(&format!("{:>6}  {:<24} {}\n", line.address, words.join(","), line.text));
+    }
+    text
+}
+
+/// A printable listing with addresses, raw words and instructions
+pub fn listing(program: &[i64]) -> String {
+    format(&disassemble(program), &BTreeMap::new())
+}
+
+/// A listing that labels named addresses and refers to them by name
+pub fn symbolic_listing(image: &Image) -> String {
+    format(&lines(&image.words, &image.symbols, &opcode::table()), &image.symbols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instructions_and_data() {
+        let lines = disassemble(&[1002,4,3,4, 33, 1105,1,0, 99, 7]);
+        let text: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(text, vec!["mul [4], 3, [4]", "data 33", "jnz 1, 0", "hlt", "data 7"]);
+        assert_eq!(lines[2].address, 5);
+        assert_eq!(lines[2].words, vec![1105, 1, 0]);
+    }
+
+    #[test]
+    fn relative_operands() {
+        let text: Vec<String> = disassemble(&[109,4, 21201,-1,5,2, 99]).into_iter().map(|l| l.text).collect();
+        assert_eq!(text, vec!["arb 4", "add [rb-1], 5, [rb+2]", "hlt"]);
+    }
+
+    #[test]
+    fn symbols() {
+        let mut image = Image::new(vec![1005,7,5, 99, 0, 4,7, 3]);
+        image.symbols.insert(5, String::from("print"));
+        image.symbols.insert(7, String::from("count"));
+        let listing = symbolic_listing(&image);
+        let lines: Vec<&str> = listing.lines().collect();
+        assert!(lines[0].ends_with("jnz [count], print"));
+        assert_eq!(lines[3], "print:");
+        assert!(lines[4].ends_with("out [count]"));
+    }
+
+    #[test]
+    fn truncated_instruction() {
+        let text: Vec<String> = disassemble(&[1, 0, 0]).into_iter().map(|l| l.text).collect();
+        assert_eq!(text, vec!["data 1", "data 0", "data 0"]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Randomised testing of the intcode backends.
+//!
+//! Well-formed programs are generated with bounded loops, so they always
+//! halt, and checked against a small reference interpreter. Malformed
+//! programs, random words or mutated well-formed ones, mustn't panic or run
+//! forever, and both backends must agree on them; those that start with an
+//! unknown opcode must make `execute` return an error.
+//!
+//! Everything is driven by a seeded generator. A failure reports the seed of
+//! the case, which reruns alone with
+//! `INTCODE_FUZZ_SEED=<seed> INTCODE_FUZZ_CASES=1 cargo test --lib fuzz`.
+
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+
+use super::{disassemble, execute, execute_limited, optimize, threaded, Error, InputProvider, OutputCollector};
+
+const DEFAULT_SEED: u64 = 0x1c0de;
+
+/// Words of data after a generated program's code
+const DATA: usize = 16;
+/// Data cells reserved for loop counters, one per nesting level
+const COUNTERS: usize = 3;
+
+/// Instructions a malformed program may run before it counts as hanging
+const STEP_LIMIT: u64 = 10_000;
+
+/// xorshift64*, which is plenty for picking test cases
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below((high - low + 1) as u64) as i64
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// The seed of every case to run
+fn seeds(default_cases: usize) -> impl Iterator<Item=u64> {
+    let seed = env_or("INTCODE_FUZZ_SEED", DEFAULT_SEED);
+    (0..env_or("INTCODE_FUZZ_CASES", default_cases) as u64).map(move |i| seed.wrapping_add(i))
+}
+
+#[derive(Clone, Copy)]
+enum Arg {
+    Immediate(i64),
+    /// A data cell by position
+    Cell(usize),
+    /// A data cell relative to the base, which points at the data
+    Relative(usize)
+}
+
+struct Generator {
+    rng: Rng,
+    code: Vec<i64>,
+    /// Words holding data cell numbers, to become addresses once the code is done
+    cells: Vec<usize>
+}
+
+impl Generator {
+    fn emit(&mut self, opcode: i64, args: &[Arg]) {
+        let mut word = opcode;
+        let mut scale = 100;
+        let at = self.code.len();
+        self.code.push(0);
+
+        for arg in args {
+            let (mode, value) = match *arg {
+                Arg::Immediate(v) => (1, v),
+                Arg::Cell(c) => {
+                    self.cells.push(self.code.len());
+                    (0, c as i64)
+                },
+                Arg::Relative(c) => (2, c as i64)
+            };
+            word += mode * scale;
+            scale *= 10;
+            self.code.push(value);
+        }
+
+        self.code[at] = word;
+    }
+
+    fn cell(&mut self, cell: usize) -> Arg {
+        if self.rng.below(2) == 0 { Arg::Cell(cell) } else { Arg::Relative(cell) }
+    }
+
+    fn read(&mut self) -> Arg {
+        match self.rng.below(8) {
+            0 => Arg::Immediate(self.rng.next() as i64),
+            1..=3 => Arg::Immediate(self.rng.range(-10, 10)),
+            _ => {
+                let cell = self.rng.below(DATA as u64) as usize;
+                self.cell(cell)
+            }
+        }
+    }
+
+    /// A cell that isn't a loop counter
+    fn write(&mut self) -> Arg {
+        let cell = self.rng.range(COUNTERS as i64, DATA as i64 - 1) as usize;
+        self.cell(cell)
+    }
+
+    fn block(&mut self, depth: usize) {
+        let length = if depth == 0 { self.rng.range(4, 16) } else { self.rng.range(1, 5) };
+        for _ in 0..length {
+            match self.rng.below(10) {
+                0..=3 => {
+                    let opcode = [1, 2, 7, 8][self.rng.below(4) as usize];
+                    let args = [self.read(), self.read(), self.write()];
+                    self.emit(opcode, &args);
+                },
+                4 => {
+                    let args = [self.write()];
+                    self.emit(3, &args);
+                },
+                5 | 6 => {
+                    let args = [self.read()];
+                    self.emit(4, &args);
+                },
+                7 if depth < COUNTERS => {
+                    // Skip forward over a nested block
+                    let opcode = 5 + self.rng.below(2) as i64;
+                    let args = [self.read(), Arg::Immediate(0)];
+                    self.emit(opcode, &args);
+                    let target = self.code.len() - 1;
+                    self.block(depth + 1);
+                    self.code[target] = self.code.len() as i64;
+                },
+                8 if depth < COUNTERS => {
+                    let counter = self.cell(depth);
+                    let count = self.rng.range(1, 4);
+                    self.emit(1, &[Arg::Immediate(count), Arg::Immediate(0), counter]);
+                    let top = self.code.len() as i64;
+                    self.block(depth + 1);
+                    self.emit(1, &[counter, Arg::Immediate(-1), counter]);
+                    self.emit(5, &[counter, Arg::Immediate(top)]);
+                },
+                _ => {}
+            };
+        }
+    }
+}
+
+/// A program that halts after a bounded number of steps, with some input
+fn well_formed(rng: Rng) -> (Vec<i64>, Vec<i64>) {
+    let mut generator = Generator { rng, code: Vec::new(), cells: Vec::new() };
+
+    // Point the relative base at the data, once we know where it is
+    generator.emit(9, &[Arg::Immediate(0)]);
+    generator.block(0);
+    generator.code.push(99);
+
+    let data = generator.code.len();
+    generator.code[1] = data as i64;
+    for at in generator.cells {
+        generator.code[at] += data as i64;
+    }
+
+    let mut rng = generator.rng;
+    let mut program = generator.code;
+    program.extend((0..DATA).map(|_| rng.range(-100, 100)));
+
+    let inputs = (0..rng.below(16)).map(|_| rng.range(-1000, 1000)).collect();
+    (program, inputs)
+}
+
+/// A word that may or may not be meaningful
+fn junk(rng: &mut Rng) -> i64 {
+    match rng.below(6) {
+        0 => {
+            let codes = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99, 0, 42];
+            let code = codes[rng.below(codes.len() as u64) as usize];
+            code + 100 * rng.range(0, 3) + 1000 * rng.range(0, 3) + 10000 * rng.range(0, 3)
+        },
+        1 | 2 => rng.range(-5, 40),
+        3 => rng.next() as i64,
+        4 => rng.range(-1000, 1000),
+        _ => rng.range(1, 9)
+    }
+}
+
+/// A program that may or may not fail, with some input, and whether it's
+/// sure to
+fn malformed(mut rng: Rng) -> (Vec<i64>, Vec<i64>, bool) {
+    let kind = rng.below(3);
+    let mut program: Vec<i64> = if kind == 2 {
+        let (program, _) = well_formed(Rng::new(rng.next()));
+        program
+    }
+    else {
+        let length = rng.range(1, 40);
+        (0..length).map(|_| junk(&mut rng)).collect()
+    };
+
+    match kind {
+        // Codes 10 to 98 mean nothing, whatever the modes
+        0 => program[0] = rng.range(10, 98) + 100 * rng.range(0, 222),
+        1 => {},
+        _ => for _ in 0..rng.range(1, 4) {
+            let at = rng.below(program.len() as u64) as usize;
+            program[at] = junk(&mut rng);
+        }
+    };
+
+    let inputs = (0..rng.below(4)).map(|_| rng.range(-10, 10)).collect();
+    (program, inputs, kind == 0)
+}
+
+/// A deliberately plain interpreter for the standard opcodes, written
+/// straight from the puzzle text. Returns the address of an input
+/// instruction that found no input left.
+fn reference(memory: &mut Vec<i64>, inputs: &[i64], outputs: &mut Vec<i64>) -> Result<(), usize> {
+    let mut inputs = inputs.iter();
+    let mut pc = 0;
+    let mut base = 0;
+
+    // An immediate parameter is addressed by its own position
+    fn address(memory: &[i64], pc: usize, base: i64, n: usize) -> usize {
+        let value = memory[pc + n];
+        match memory[pc] / 10i64.pow(n as u32 + 1) % 10 {
+            0 => value as usize,
+            1 => pc + n,
+            2 => (base + value) as usize,
+            mode => panic!("Generated mode {}", mode)
+        }
+    }
+
+    while pc < memory.len() {
+        let a = |n| address(memory, pc, base, n);
+        let get = |n| memory.get(a(n)).copied().unwrap_or(0);
+
+        let (store, next) = match memory[pc] % 100 {
+            1 => (Some((a(3), get(1).wrapping_add(get(2)))), pc + 4),
+            2 => (Some((a(3), get(1).wrapping_mul(get(2)))), pc + 4),
+            3 => match inputs.next() {
+                Some(value) => (Some((a(1), *value)), pc + 2),
+                None => return Err(pc)
+            },
+            4 => {
+                outputs.push(get(1));
+                (None, pc + 2)
+            },
+            5 => (None, if get(1) != 0 { get(2) as usize } else { pc + 3 }),
+            6 => (None, if get(1) == 0 { get(2) as usize } else { pc + 3 }),
+            7 => (Some((a(3), (get(1) < get(2)) as i64)), pc + 4),
+            8 => (Some((a(3), (get(1) == get(2)) as i64)), pc + 4),
+            9 => {
+                base += get(1);
+                (None, pc + 2)
+            },
+            99 => return Ok(()),
+            op => panic!("Generated opcode {}", op)
+        };
+
+        if let Some((address, value)) = store {
+            if address >= memory.len() {
+                memory.resize(address + 1, 0);
+            }
+            memory[address] = value;
+        }
+        pc = next;
+    }
+
+    Ok(())
+}
+
+/// Outputs, final memory and result of running a backend
+type Run = (Vec<i64>, Vec<i64>, Result<u64, String>);
+
+fn run<F>(backend: F, program: &[i64], inputs: &[i64]) -> Run
+    where F: Fn(&mut Vec<i64>, &mut InputProvider, &mut OutputCollector) -> Result<u64, Error>
+{
+    let mut memory = program.to_vec();
+    let mut output = OutputCollector::new();
+    let result = backend(&mut memory, &mut InputProvider::new(inputs.to_vec()), &mut output);
+    (output.outputs, memory, result.map_err(|e| e.to_string()))
+}
+
+/// Run `check` on one case, reporting its seed if it panics
+fn case<F: FnOnce()>(seed: u64, program: &[i64], check: F) {
+    if let Err(e) = panic::catch_unwind(AssertUnwindSafe(check)) {
+        let message = e.downcast_ref::<String>().map(String::as_str)
+            .or_else(|| e.downcast_ref::<&str>().copied())
+            .unwrap_or("panic");
+        panic!("Seed {}: {}\nProgram: {:?}", seed, message, program);
+    }
+}
+
+#[test]
+fn matches_reference() {
+    for seed in seeds(500) {
+        let (program, inputs) = well_formed(Rng::new(seed));
+
+        case(seed, &program, || {
+            let mut memory = program.clone();
+            let mut outputs = Vec::new();
+            let expected = reference(&mut memory, &inputs, &mut outputs)
+                .map_err(|pc| Error::InputExhausted { pc }.to_string());
+
+            for backend in &[execute, threaded::execute] {
+                let (actual_outputs, actual_memory, result) =
+                    run(|p, i, o| backend(p, i, o).map(|_| 0), &program, &inputs);
+
+                assert_eq!(actual_outputs, outputs);
+                assert_eq!(actual_memory, memory);
+                assert_eq!(result.map(|_| ()), expected);
+            }
+
+            // The optimiser may not change what a program outputs
+            let (optimized, _, result) =
+                run(|p, i, o| execute(&mut optimize::optimize(p), i, o).map(|_| 0), &program, &inputs);
+            assert_eq!(optimized, outputs);
+            assert_eq!(result.is_ok(), expected.is_ok());
+        });
+    }
+}
+
+#[test]
+fn rejects_malformed() {
+    for seed in seeds(2000) {
+        let (program, inputs, bad) = malformed(Rng::new(seed));
+
+        case(seed, &program, || {
+            let interpreted = run(|p, i, o| execute_limited(p, i, o, STEP_LIMIT), &program, &inputs);
+            let compiled = run(|p, i, o| threaded::execute_limited(p, i, o, STEP_LIMIT), &program, &inputs);
+            assert_eq!(interpreted, compiled);
+            if bad {
+                assert_eq!(interpreted.2, Err(Error::UnknownOpcode { pc: 0, word: program[0] }.to_string()));
+            }
+
+            // The tools must cope with anything too
+            optimize::optimize(&program);
+            disassemble::disassemble(&program);
+        });
+    }
+}
@@ -0,0 +1,272 @@
+//! A compact binary format for intcode programs.
+//!
+//! ```text
+//! magic     "INTC"
+//! version   u8, currently 1
+//! flags     u8, bit 0 set if a symbol table follows the words; no others
+//! count     varint, number of words
+//! entry     varint, address execution starts at, below count unless 0
+//! words     count zigzag varints
+//! symbols   varint count, then per symbol a varint address below count,
+//!           a varint length and that many bytes of UTF-8 name
+//! ```
+//!
+//! Varints are LEB128: seven bits per byte, least significant first, with the
+//! high bit set on every byte but the last. Words are zigzag encoded first so
+//! small negative numbers stay short.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Read, Write};
+
+pub const MAGIC: &[u8; 4] = b"INTC";
+#[cfg(feature = "std")]
+pub const VERSION: u8 = 1;
+
+#[cfg(feature = "std")]
+const HAS_SYMBOLS: u8 = 1;
+
+/// A program image with the address it starts at and optional names
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub words: Vec<i64>,
+    pub entry: usize,
+    pub symbols: BTreeMap<usize, String>
+}
+
+impl Image {
+    pub fn new(words: Vec<i64>) -> Image {
+        Image { words, entry: 0, symbols: BTreeMap::new() }
+    }
+
+    #[cfg(feature = "std")]
+    /// Execute the image from its entry point
+    pub fn execute<I,O>(&mut self, input: &mut I, output: &mut O) -> Result<(), super::Error> where
+        I: BufRead, O: Write
+    {
+        let registers = super::Registers { pc: self.entry, base: 0, steps: 0 };
+        super::execute_from(&mut self.words, registers, u64::MAX, super::opcode::table(), input, output).map(|_| ())
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedVersion(u8),
+    /// Flag bits this version doesn't define
+    UnknownFlags(u8),
+    /// A varint ran past ten bytes, or past 64 bits
+    Overlong,
+    /// An entry point past the last word
+    BadEntry(usize),
+    /// A symbol at an address past the last word
+    BadSymbolAddress(usize),
+    /// A symbol whose name isn't UTF-8
+    BadSymbol(usize)
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "{}", e),
+            ImageError::BadMagic => write!(f, "Not an intcode image"),
+            ImageError::UnsupportedVersion(v) => write!(f, "Unsupported image version {}", v),
+            ImageError::UnknownFlags(flags) => write!(f, "Unknown image flags {:#04x}", flags),
+            ImageError::Overlong => write!(f, "Malformed varint"),
+            ImageError::BadEntry(entry) => write!(f, "Entry point {} is outside the program", entry),
+            ImageError::BadSymbolAddress(a) => write!(f, "Symbol at address {} is outside the program", a),
+            ImageError::BadSymbol(a) => write!(f, "Symbol at address {} is not UTF-8", a)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ImageError {
+    fn from(e: io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_varint<W: Write>(w: &mut W, mut n: u64) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(10);
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            bytes.push(byte);
+            return w.write_all(&bytes);
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[cfg(feature = "std")]
+fn read_varint<R: Read>(r: &mut R) -> Result<u64, ImageError> {
+    let mut n: u64 = 0;
+    for shift in (0..70).step_by(7) {
+        let mut byte = [0];
+        r.read_exact(&mut byte)?;
+
+        let bits = u64::from(byte[0] & 0x7f);
+        if shift == 63 && bits > 1 {
+            return Err(ImageError::Overlong);
+        }
+        n |= bits << shift;
+
+        if byte[0] & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(ImageError::Overlong)
+}
+
+#[cfg(feature = "std")]
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+#[cfg(feature = "std")]
+fn unzigzag(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+#[cfg(feature = "std")]
+pub fn save<W: Write>(image: &Image, w: &mut W) -> io::Result<()> {
+    let flags = if image.symbols.is_empty() { 0 } else { HAS_SYMBOLS };
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION, flags])?;
+    write_varint(w, image.words.len() as u64)?;
+    write_varint(w, image.entry as u64)?;
+
+    for word in &image.words {
+        write_varint(w, zigzag(*word))?;
+    }
+
+    if flags & HAS_SYMBOLS != 0 {
+        write_varint(w, image.symbols.len() as u64)?;
+        for (address, name) in &image.symbols {
+            write_varint(w, *address as u64)?;
+            write_varint(w, name.len() as u64)?;
+            w.write_all(name.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Load an image. Reads are buffered, so `r` may be read past the image's
+/// end.
+#[cfg(feature = "std")]
+pub fn load<R: Read>(r: &mut R) -> Result<Image, ImageError> {
+    let r = &mut io::BufReader::new(r);
+    let mut header = [0; 6];
+    r.read_exact(&mut header)?;
+
+    if &header[..4] != MAGIC {
+        return Err(ImageError::BadMagic);
+    }
+    if header[4] != VERSION {
+        return Err(ImageError::UnsupportedVersion(header[4]));
+    }
+    let flags = header[5];
+    if flags & !HAS_SYMBOLS != 0 {
+        return Err(ImageError::UnknownFlags(flags));
+    }
+
+    let count = read_varint(r)? as usize;
+    let entry = read_varint(r)? as usize;
+    // An empty program can still start at 0, and halts there
+    if entry >= count.max(1) {
+        return Err(ImageError::BadEntry(entry));
+    }
+
+    // The count is untrusted, so don't let it size the allocation
+    let mut words = Vec::with_capacity(count.min(1 << 16));
+    for _ in 0..count {
+        words.push(unzigzag(read_varint(r)?));
+    }
+
+    let mut symbols = BTreeMap::new();
+    if flags & HAS_SYMBOLS != 0 {
+        for _ in 0..read_varint(r)? {
+            let address = read_varint(r)? as usize;
+            if address >= count {
+                return Err(ImageError::BadSymbolAddress(address));
+            }
+            let length = read_varint(r)?;
+
+            let mut name = Vec::new();
+            r.take(length).read_to_end(&mut name)?;
+            if name.len() as u64 != length {
+                return Err(ImageError::Io(io::Error::from(io::ErrorKind::UnexpectedEof)));
+            }
+
+            match String::from_utf8(name) {
+                Ok(name) => symbols.insert(address, name),
+                Err(_) => return Err(ImageError::BadSymbol(address))
+            };
+        }
+    }
+
+    Ok(Image { words, entry, symbols })
+}
+
+/// Whether `bytes` start like an image rather than program text
+pub fn is_image(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    fn round_trip(image: &Image) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        save(image, &mut bytes).unwrap();
+        assert_eq!(&load(&mut bytes.as_slice()).unwrap(), image);
+        bytes
+    }
+
+    #[test]
+    fn words() {
+        let words = vec![0, 1, -1, 63, -64, 64, 1 << 40, i64::MAX, i64::MIN];
+        round_trip(&Image::new(words));
+        round_trip(&Image::new(Vec::new()));
+    }
+
+    #[test]
+    fn symbols_and_entry() {
+        let mut image = Image::new(vec![1105, 1, 4, 99, 104, 7, 99]);
+        image.entry = 4;
+        image.symbols.insert(0, String::from("start"));
+        image.symbols.insert(4, String::from("main"));
+        round_trip(&image);
+    }
+
+    #[test]
+    fn smaller_than_text() {
+        let text = include_str!("../../input/5.txt");
+        let bytes = round_trip(&Image::new(super::super::parse_input(text).unwrap()));
+        assert!(bytes.len() * 2 < text.len());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(load(&mut &b"INTX\x01\x00"[..]), Err(ImageError::BadMagic)));
+        assert!(matches!(load(&mut &b"INTC\x02\x00"[..]), Err(ImageError::UnsupportedVersion(2))));
+        assert!(matches!(load(&mut &b"INTC\x01\x00\x02\x00\x01"[..]), Err(ImageError::Io(_))));
+        assert!(matches!(load(&mut &b"INTC\x01\x02\x01\x00\x00"[..]), Err(ImageError::UnknownFlags(2))));
+        assert!(matches!(load(&mut &b"INTC\x01\x00\x01\x01\x00"[..]), Err(ImageError::BadEntry(1))));
+        assert!(matches!(load(&mut &b"INTC\x01\x01\x01\x00\x00\x01\x01\x00"[..]), Err(ImageError::BadSymbolAddress(1))));
+        assert!(matches!(load(&mut &b"INTC\x01\x00\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01"[..]),
+                         Err(ImageError::Overlong)));
+    }
+}
@@ -0,0 +1,401 @@
+#[cfg(feature = "std")]
+pub mod compiler;
+pub mod condition;
+#[cfg(all(test, feature = "std"))]
+mod conformance;
+pub mod coverage;
+pub mod disassemble;
+#[cfg(all(test, feature = "std"))]
+mod fuzz;
+mod image;
+pub mod observer;
+pub mod opcode;
+#[cfg(feature = "std")]
+pub mod optimize;
+mod parse;
+#[cfg(feature = "std")]
+pub mod server;
+#[cfg(feature = "std")]
+mod streams;
+#[cfg(feature = "std")]
+pub mod threaded;
+#[cfg(feature = "std")]
+pub mod transcript;
+
+pub use self::image::{is_image, Image};
+#[cfg(feature = "std")]
+pub use self::image::{load, save, ImageError};
+pub use self::parse::{parse_input, parse_programs, ParseError};
+#[cfg(feature = "std")]
+pub use self::streams::{execute, execute_limited, InputProvider, OutputCollector};
+#[cfg(feature = "std")]
+use self::streams::{execute_from, read_value, write_value};
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use self::observer::{Control, Observer, State};
+use self::opcode::{Effect, Opcode, Role, Table, MAX_PARAMS};
+
+/// The most words of memory a program may address
+pub const MEMORY_LIMIT: usize = 1 << 20;
+
+/// Why a program stopped without halting
+#[derive(Debug)]
+pub enum Error {
+    /// An instruction word with no registered opcode
+    UnknownOpcode { pc: usize, word: i64 },
+    /// An instruction word with an addressing mode other than 0, 1 or 2
+    BadMode { pc: usize, word: i64 },
+    /// The program ends part way through an instruction
+    Truncated { pc: usize },
+    /// An address below zero or beyond `MEMORY_LIMIT`
+    BadAddress { pc: usize, address: i64 },
+    /// The program asked for more input than there was
+    InputExhausted { pc: usize },
+    /// A line of input that isn't a number
+    BadInput { pc: usize, text: String },
+    /// An opcode whose semantics store or input, but with no `Write` parameter
+    NoWriteParameter { pc: usize, code: i64 },
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The program ran this many instructions without halting
+    StepLimit(u64)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::UnknownOpcode { pc, word } => write!(f, "Unknown opcode {} at {}", word, pc),
+            Error::BadMode { pc, word } => write!(f, "Bad addressing mode in {} at {}", word, pc),
+            Error::Truncated { pc } => write!(f, "Truncated instruction at {}", pc),
+            Error::BadAddress { pc, address } => write!(f, "Bad address {} at {}", address, pc),
+            Error::InputExhausted { pc } => write!(f, "Input exhausted at {}", pc),
+            Error::BadInput { pc, text } => write!(f, "Bad input `{}` at {}", text, pc),
+            Error::NoWriteParameter { pc, code } => write!(f, "Opcode {} at {} has nowhere to store", code, pc),
+            #[cfg(feature = "std")]
+            Error::Io(e) => write!(f, "{}", e),
+            Error::StepLimit(limit) => write!(f, "No halt after {} steps", limit)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+#[derive(Debug)]
+enum AddressingMode {
+    Immediate,
+    Position,
+    /// An address relative to the relative base
+    Relative
+}
+
+#[derive(Debug)]
+struct Parameter {
+    value: i64,
+    mode: AddressingMode
+}
+
+#[derive(Debug)]
+struct Instruction {
+    opcode: Opcode,
+    params: Vec<Parameter>
+}
+
+/// Decode the instruction at `pc`, which must be within `program`
+fn decode(program: &[i64], pc: usize, table: &Table) -> Result<Instruction, Error> {
+    let word = program[pc];
+    let opcode = *table.get(word).ok_or(Error::UnknownOpcode { pc, word })?;
+
+    let mut modes = word / 100;
+    let mut params = Vec::with_capacity(opcode.params.len());
+
+    for i in 1..opcode.length() {
+        let mode = match modes % 10 {
+            0 => AddressingMode::Position,
+            1 => AddressingMode::Immediate,
+            2 => AddressingMode::Relative,
+            _ => return Err(Error::BadMode { pc, word })
+        };
+        let value = *program.get(pc + i).ok_or(Error::Truncated { pc })?;
+
+        params.push(Parameter { value, mode });
+        modes /= 10;
+    }
+
+    Ok(Instruction { opcode, params })
+}
+
+/// Decode the instruction at `pc`, if there is a well-formed one
+fn try_decode(program: &[i64], pc: usize, table: &Table) -> Option<Instruction> {
+    if pc < program.len() { decode(program, pc, table).ok() } else { None }
+}
+
+/// Check an address is within the memory a program may use
+fn address(value: i64, pc: usize) -> Result<usize, Error> {
+    if value < 0 || value >= MEMORY_LIMIT as i64 {
+        return Err(Error::BadAddress { pc, address: value });
+    }
+    Ok(value as usize)
+}
+
+/// The address a write parameter stores to. Writes are never immediate, so
+/// an immediate mode is read as position mode.
+fn output_address(param: &Parameter, base: i64, pc: usize) -> Result<usize, Error> {
+    match param.mode {
+        AddressingMode::Relative => address(base.saturating_add(param.value), pc),
+        _ => address(param.value, pc)
+    }
+}
+
+/// The address a read parameter refers to, or `None` if it's immediate
+fn input_address(param: &Parameter, base: i64, pc: usize) -> Result<Option<usize>, Error> {
+    match param.mode {
+        AddressingMode::Immediate => Ok(None),
+        AddressingMode::Position => address(param.value, pc).map(Some),
+        AddressingMode::Relative => address(base.saturating_add(param.value), pc).map(Some)
+    }
+}
+
+/// Store to memory, growing it with zeroes if `address` is past the end
+fn store(program: &mut Vec<i64>, address: usize, value: i64) {
+    if address >= program.len() {
+        program.resize(address + 1, 0);
+    }
+    program[address] = value;
+}
+
+/// Where execution is up to
+#[derive(Debug, Clone, Copy)]
+struct Registers {
+    pc: usize,
+    /// The relative base
+    base: i64,
+    /// Instructions executed so far
+    steps: u64
+}
+
+/// Why a `Machine` stopped running
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    /// An input instruction found no queued input. Running again after
+    /// `push_input` executes it.
+    Input,
+    Output(i64),
+    /// The observer asked to pause. Running again carries on.
+    Pause,
+    Halt
+}
+
+/// An intcode machine that runs until it needs input, produces output or
+/// halts, so the caller decides where values come from and go to. `O` is
+/// told about everything it does; see `observer`.
+pub struct Machine<O = ()> {
+    memory: Vec<i64>,
+    registers: Registers,
+    limit: u64,
+    inputs: VecDeque<i64>,
+    table: Arc<Table>,
+    halted: bool,
+    observer: O,
+    /// Paused before the instruction at pc, which the observer has already seen
+    resuming: bool
+}
+
+impl Machine {
+    pub fn new(program: Vec<i64>) -> Machine {
+        Machine::resume(program, Registers { pc: 0, base: 0, steps: 0 }, u64::MAX, opcode::table())
+    }
+
+    /// A machine taking over at `registers`, e.g. from another backend
+    fn resume(memory: Vec<i64>, registers: Registers, limit: u64, table: Arc<Table>) -> Machine {
+        Machine {
+            memory, registers, limit, table,
+            inputs: VecDeque::new(),
+            halted: false,
+            observer: (),
+            resuming: false
+        }
+    }
+}
+
+impl<O: Observer> Machine<O> {
+    /// Fail with `Error::StepLimit` rather than run more than `limit` instructions in all
+    pub fn with_limit(mut self, limit: u64) -> Machine<O> {
+        self.limit = limit;
+        self
+    }
+
+    /// Start at `pc` rather than address zero, e.g. at an image's entry point
+    pub fn starting_at(mut self, pc: usize) -> Machine<O> {
+        self.registers.pc = pc;
+        self
+    }
+
+    /// Decode with `table` rather than the registered opcodes
+    pub fn with_table(mut self, table: Table) -> Machine<O> {
+        self.table = Arc::new(table);
+        self
+    }
+
+    /// Report to `observer` rather than the current observer
+    pub fn with_observer<P: Observer>(self, observer: P) -> Machine<P> {
+        let Machine { memory, registers, limit, inputs, table, halted, resuming, .. } = self;
+        Machine { memory, registers, limit, inputs, table, halted, observer, resuming }
+    }
+
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Queue a value for input instructions to read
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    /// Instructions executed so far
+    pub fn steps(&self) -> u64 {
+        self.registers.steps
+    }
+
+    pub fn pc(&self) -> usize {
+        self.registers.pc
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    pub fn into_memory(self) -> Vec<i64> {
+        self.memory
+    }
+
+    /// Run until the next event. Memory grows as the program writes past its end.
+    pub fn run(&mut self) -> Result<Event, Error> {
+        while !self.halted && self.registers.pc < self.memory.len() {
+            if self.registers.steps >= self.limit {
+                return Err(Error::StepLimit(self.limit));
+            }
+
+            let instruction = decode(&self.memory, self.registers.pc, &self.table)?;
+            if let Some(event) = self.execute(&instruction)? {
+                return Ok(event);
+            }
+        }
+
+        self.halted = true;
+        Ok(Event::Halt)
+    }
+
+    /// Execute one instruction, unless it's waiting for input or the
+    /// observer pauses before it
+    fn execute(&mut self, instruction: &Instruction) -> Result<Option<Event>, Error> {
+        let Registers { pc, base, steps } = self.registers;
+        let opcode = &instruction.opcode;
+        let mut values = [0; MAX_PARAMS];
+        let mut count = 0;
+        let mut reads = [(0, 0); MAX_PARAMS];
+        let mut read_count = 0;
+        let mut write = None;
+
+        for (param, role) in instruction.params.iter().zip(opcode.params) {
+            match role {
+                Role::Read | Role::Jump => {
+                    // Memory beyond the end of the program reads as zero
+                    values[count] = match input_address(param, base, pc)? {
+                        Some(a) => {
+                            let value = self.memory.get(a).copied().unwrap_or(0);
+                            reads[read_count] = (a, value);
+                            read_count += 1;
+                            value
+                        },
+                        None => param.value
+                    };
+                    count += 1;
+                },
+                Role::Write => {
+                    if write.is_none() {
+                        write = Some(output_address(param, base, pc)?);
+                    }
+                }
+            };
+        }
+
+        let effect = (opcode.semantics)(&values[..count]);
+        let destination = match (effect, write) {
+            (Effect::Store(_), None) | (Effect::Input, None) => return Err(Error::NoWriteParameter { pc, code: opcode.code }),
+            // Only stores and inputs use it
+            (_, write) => write.unwrap_or_default()
+        };
+
+        if effect == Effect::Input && self.inputs.is_empty() {
+            return Ok(Some(Event::Input));
+        }
+
+        if !self.resuming && self.observer.before(pc, opcode, &State { pc, base, steps, memory: &self.memory }) == Control::Pause {
+            self.resuming = true;
+            return Ok(Some(Event::Pause));
+        }
+        self.resuming = false;
+
+        let mut control = Control::Continue;
+        for (a, value) in &reads[..read_count] {
+            control = control.or(self.observer.read(*a, *value));
+        }
+
+        self.registers.steps += 1;
+        let next = pc + opcode.length();
+        let mut event = None;
+
+        self.registers.pc = match effect {
+            Effect::Store(v) => {
+                let a = destination;
+                store(&mut self.memory, a, v);
+                control = control.or(self.observer.write(a, v));
+                next
+            },
+            Effect::Input => {
+                let (a, value) = (destination, self.inputs.pop_front().unwrap());
+                control = control.or(self.observer.input(value));
+                store(&mut self.memory, a, value);
+                control = control.or(self.observer.write(a, value));
+                next
+            },
+            Effect::Output(v) => {
+                control = control.or(self.observer.output(v));
+                event = Some(Event::Output(v));
+                next
+            },
+            Effect::Jump(target) => address(target, pc)?,
+            Effect::AdjustBase(offset) => {
+                self.registers.base = base.wrapping_add(offset);
+                next
+            },
+            Effect::Next => next,
+            Effect::Halt => {
+                self.halted = true;
+                event = Some(Event::Halt);
+                pc
+            }
+        };
+
+        let Registers { pc: next, base, steps } = self.registers;
+        control = control.or(self.observer.after(pc, opcode, effect, &State { pc: next, base, steps, memory: &self.memory }));
+
+        if event.is_none() && control == Control::Pause {
+            event = Some(Event::Pause);
+        }
+        Ok(event)
+    }
+}
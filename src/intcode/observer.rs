@@ -0,0 +1,285 @@
+//! Hooks into a running `Machine`.
+//!
+//! An `Observer` is told about every instruction a machine executes and
+//! every value it reads, writes, inputs and outputs, and can ask the machine
+//! to pause. Tracing, profiling, coverage and watchpoints are all observers.
+//!
+//! A machine has one observer, by default `()`, which ignores everything:
+//! its callbacks inline to nothing, so an unobserved machine runs as fast as
+//! one without hooks. To attach several, combine them in a tuple, which
+//! calls both in order, or a `Vec` for a number only known at run time.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::opcode::{Effect, Opcode};
+
+/// Whether an observer wants the machine to keep going
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    Continue,
+    /// Stop at the next instruction boundary, with `Event::Pause`
+    Pause
+}
+
+impl Control {
+    /// Pause if either wants to
+    pub fn or(self, other: Control) -> Control {
+        if self == Control::Pause { self } else { other }
+    }
+}
+
+/// The machine as an observer sees it
+#[derive(Debug, Clone, Copy)]
+pub struct State<'a> {
+    pub pc: usize,
+    /// The relative base
+    pub base: i64,
+    /// Instructions executed so far
+    pub steps: u64,
+    pub memory: &'a [i64]
+}
+
+/// Callbacks from a running machine, each defaulting to doing nothing.
+///
+/// For each instruction the machine calls `before`, then `read` for each
+/// value it reads from memory, then `write`, `input` or `output` as the
+/// instruction does those, then `after`. Pausing from `before` stops the
+/// machine with the instruction not yet executed, and running again
+/// executes it without calling `before` a second time. Pausing from any
+/// other callback stops the machine after the instruction, unless the
+/// instruction output a value or halted, which stops it anyway.
+pub trait Observer {
+    /// The instruction at `pc` is about to execute
+    fn before(&mut self, _pc: usize, _opcode: &Opcode, _state: &State) -> Control {
+        Control::Continue
+    }
+
+    /// The instruction at `pc` executed, leaving the machine in `state`
+    fn after(&mut self, _pc: usize, _opcode: &Opcode, _effect: Effect, _state: &State) -> Control {
+        Control::Continue
+    }
+
+    /// A parameter read `value` from `address`
+    fn read(&mut self, _address: usize, _value: i64) -> Control {
+        Control::Continue
+    }
+
+    /// `value` was stored at `address`, by an instruction or input
+    fn write(&mut self, _address: usize, _value: i64) -> Control {
+        Control::Continue
+    }
+
+    fn input(&mut self, _value: i64) -> Control {
+        Control::Continue
+    }
+
+    fn output(&mut self, _value: i64) -> Control {
+        Control::Continue
+    }
+}
+
+impl Observer for () {}
+
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn before(&mut self, pc: usize, opcode: &Opcode, state: &State) -> Control {
+        self.0.before(pc, opcode, state).or(self.1.before(pc, opcode, state))
+    }
+
+    fn after(&mut self, pc: usize, opcode: &Opcode, effect: Effect, state: &State) -> Control {
+        self.0.after(pc, opcode, effect, state).or(self.1.after(pc, opcode, effect, state))
+    }
+
+    fn read(&mut self, address: usize, value: i64) -> Control {
+        self.0.read(address, value).or(self.1.read(address, value))
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Control {
+        self.0.write(address, value).or(self.1.write(address, value))
+    }
+
+    fn input(&mut self, value: i64) -> Control {
+        self.0.input(value).or(self.1.input(value))
+    }
+
+    fn output(&mut self, value: i64) -> Control {
+        self.0.output(value).or(self.1.output(value))
+    }
+}
+
+/// Call every observer, pausing if any of them wants to
+fn each<O, F>(observers: &mut [O], mut f: F) -> Control where
+    F: FnMut(&mut O) -> Control
+{
+    observers.iter_mut().fold(Control::Continue, |control, observer| control.or(f(observer)))
+}
+
+impl<O: Observer> Observer for Vec<O> {
+    fn before(&mut self, pc: usize, opcode: &Opcode, state: &State) -> Control {
+        each(self, |o| o.before(pc, opcode, state))
+    }
+
+    fn after(&mut self, pc: usize, opcode: &Opcode, effect: Effect, state: &State) -> Control {
+        each(self, |o| o.after(pc, opcode, effect, state))
+    }
+
+    fn read(&mut self, address: usize, value: i64) -> Control {
+        each(self, |o| o.read(address, value))
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Control {
+        each(self, |o| o.write(address, value))
+    }
+
+    fn input(&mut self, value: i64) -> Control {
+        each(self, |o| o.input(value))
+    }
+
+    fn output(&mut self, value: i64) -> Control {
+        each(self, |o| o.output(value))
+    }
+}
+
+/// Observers behind a pointer, so a machine can borrow one or hold `dyn` ones
+macro_rules! forward {
+    ($($pointer:ty),*) => {$(
+        impl<O: Observer + ?Sized> Observer for $pointer {
+            fn before(&mut self, pc: usize, opcode: &Opcode, state: &State) -> Control {
+                (**self).before(pc, opcode, state)
+            }
+
+            fn after(&mut self, pc: usize, opcode: &Opcode, effect: Effect, state: &State) -> Control {
+                (**self).after(pc, opcode, effect, state)
+            }
+
+            fn read(&mut self, address: usize, value: i64) -> Control {
+                (**self).read(address, value)
+            }
+
+            fn write(&mut self, address: usize, value: i64) -> Control {
+                (**self).write(address, value)
+            }
+
+            fn input(&mut self, value: i64) -> Control {
+                (**self).input(value)
+            }
+
+            fn output(&mut self, value: i64) -> Control {
+                (**self).output(value)
+            }
+        }
+    )*}
+}
+
+forward!(&mut O, Box<O>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Event, Machine};
+
+    /// Writes down everything it sees
+    #[derive(Default)]
+    struct Log(Vec<String>);
+
+    impl Observer for Log {
+        fn before(&mut self, pc: usize, opcode: &Opcode, state: &State) -> Control {
+            self.0.push(format!("before {} {} at step {}", opcode.mnemonic, pc, state.steps));
+            Control::Continue
+        }
+
+        fn after(&mut self, pc: usize, _: &Opcode, effect: Effect, state: &State) -> Control {
+            self.0.push(format!("after {} {:?} to {}", pc, effect, state.pc));
+            Control::Continue
+        }
+
+        fn read(&mut self, address: usize, value: i64) -> Control {
+            self.0.push(format!("read {} from {}", value, address));
+            Control::Continue
+        }
+
+        fn write(&mut self, address: usize, value: i64) -> Control {
+            self.0.push(format!("write {} to {}", value, address));
+            Control::Continue
+        }
+
+        fn input(&mut self, value: i64) -> Control {
+            self.0.push(format!("input {}", value));
+            Control::Continue
+        }
+
+        fn output(&mut self, value: i64) -> Control {
+            self.0.push(format!("output {}", value));
+            Control::Continue
+        }
+    }
+
+    /// Pauses before the instruction at an address
+    struct Breakpoint(usize);
+
+    impl Observer for Breakpoint {
+        fn before(&mut self, pc: usize, _: &Opcode, _: &State) -> Control {
+            if pc == self.0 { Control::Pause } else { Control::Continue }
+        }
+    }
+
+    /// Pauses when an address is written
+    struct Watchpoint(usize);
+
+    impl Observer for Watchpoint {
+        fn write(&mut self, address: usize, _: i64) -> Control {
+            if address == self.0 { Control::Pause } else { Control::Continue }
+        }
+    }
+
+    const PROGRAM: [i64; 10] = [3,9, 1001,9,1,9, 4,9, 99, 0];
+
+    #[test]
+    fn callbacks_in_order() {
+        let mut machine = Machine::new(PROGRAM.to_vec()).with_observer(Log::default());
+        machine.push_input(41);
+        assert_eq!(machine.run().unwrap(), Event::Output(42));
+        assert_eq!(machine.run().unwrap(), Event::Halt);
+
+        assert_eq!(machine.observer().0, [
+            "before in 0 at step 0", "input 41", "write 41 to 9", "after 0 Input to 2",
+            "before add 2 at step 1", "read 41 from 9", "write 42 to 9", "after 2 Store(42) to 6",
+            "before out 6 at step 2", "read 42 from 9", "output 42", "after 6 Output(42) to 8",
+            "before hlt 8 at step 3", "after 8 Halt to 8"
+        ]);
+    }
+
+    #[test]
+    fn waiting_for_input_is_not_executing() {
+        let mut machine = Machine::new(PROGRAM.to_vec()).with_observer(Log::default());
+        assert_eq!(machine.run().unwrap(), Event::Input);
+        assert!(machine.observer().0.is_empty());
+    }
+
+    #[test]
+    fn pauses() {
+        let mut machine = Machine::new(PROGRAM.to_vec()).with_observer((Breakpoint(6), Watchpoint(9)));
+        machine.push_input(41);
+
+        // After the input writes 9, after the add writes it, then before the output
+        for pc in &[2, 6, 6] {
+            assert_eq!(machine.run().unwrap(), Event::Pause);
+            assert_eq!(machine.pc(), *pc);
+        }
+        assert_eq!(machine.run().unwrap(), Event::Output(42));
+        assert_eq!(machine.run().unwrap(), Event::Halt);
+    }
+
+    #[test]
+    fn many_observers() {
+        let mut log = Log::default();
+        let observers: Vec<Box<dyn Observer + '_>> = vec![Box::new(Breakpoint(8)), Box::new(&mut log)];
+        let mut machine = Machine::new(PROGRAM.to_vec()).with_observer(observers);
+        machine.push_input(1);
+        assert_eq!(machine.run().unwrap(), Event::Output(2));
+        assert_eq!(machine.run().unwrap(), Event::Pause);
+        assert_eq!(machine.run().unwrap(), Event::Halt);
+        drop(machine);
+        assert_eq!(log.0.len(), 14);
+    }
+}
@@ -0,0 +1,225 @@
+//! The opcode registry.
+//!
+//! Every opcode's mnemonic, parameter roles and semantics live in one
+//! `Opcode` value. The interpreter, the threaded backend, the optimiser and
+//! the disassembler all look opcodes up here, so an opcode added with
+//! `register` is understood everywhere without further changes.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::{OnceLock, RwLock};
+
+/// The most parameters an opcode can take
+pub const MAX_PARAMS: usize = 8;
+
+/// How an instruction uses one of its parameters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// A value, honouring the parameter's addressing mode
+    Read,
+    /// An address the instruction stores to
+    Write,
+    /// A jump destination, resolved like a `Read`
+    Jump
+}
+
+/// The outcome of executing an instruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// Store a value through the write parameter and continue
+    Store(i64),
+    /// Read a value from input through the write parameter and continue
+    Input,
+    /// Write a value to output and continue
+    Output(i64),
+    /// Continue at the given address
+    Jump(i64),
+    /// Add to the relative base and continue
+    AdjustBase(i64),
+    /// Continue with the next instruction
+    Next,
+    Halt
+}
+
+/// An opcode and its semantics.
+///
+/// `semantics` is called with the resolved value of every `Read` and `Jump`
+/// parameter, in order, and must be a pure function of them: the optimiser
+/// evaluates it ahead of time when those values are constant. `Store` and
+/// `Input` effects go through the first `Write` parameter, and fail with
+/// `Error::NoWriteParameter` if there isn't one.
+#[derive(Debug, Clone, Copy)]
+pub struct Opcode {
+    pub code: i64,
+    pub mnemonic: &'static str,
+    pub params: &'static [Role],
+    pub semantics: fn(&[i64]) -> Effect
+}
+
+impl Opcode {
+    /// Words taken by an instruction, including the opcode
+    pub fn length(&self) -> usize {
+        1 + self.params.len()
+    }
+}
+
+use self::Role::{Jump, Read, Write};
+
+const STANDARD: [Opcode; 10] = [
+    Opcode { code: 1, mnemonic: "add", params: &[Read, Read, Write], semantics: |v| Effect::Store(v[0].wrapping_add(v[1])) },
+    Opcode { code: 2, mnemonic: "mul", params: &[Read, Read, Write], semantics: |v| Effect::Store(v[0].wrapping_mul(v[1])) },
+    Opcode { code: 3, mnemonic: "in", params: &[Write], semantics: |_| Effect::Input },
+    Opcode { code: 4, mnemonic: "out", params: &[Read], semantics: |v| Effect::Output(v[0]) },
+    Opcode { code: 5, mnemonic: "jnz", params: &[Read, Jump],
+             semantics: |v| if v[0] != 0 { Effect::Jump(v[1]) } else { Effect::Next } },
+    Opcode { code: 6, mnemonic: "jz", params: &[Read, Jump],
+             semantics: |v| if v[0] == 0 { Effect::Jump(v[1]) } else { Effect::Next } },
+    Opcode { code: 7, mnemonic: "lt", params: &[Read, Read, Write],
+             semantics: |v| Effect::Store(if v[0] < v[1] { 1 } else { 0 }) },
+    Opcode { code: 8, mnemonic: "eq", params: &[Read, Read, Write],
+             semantics: |v| Effect::Store(if v[0] == v[1] { 1 } else { 0 }) },
+    Opcode { code: 9, mnemonic: "arb", params: &[Read], semantics: |v| Effect::AdjustBase(v[0]) },
+    Opcode { code: 99, mnemonic: "hlt", params: &[], semantics: |_| Effect::Halt }
+];
+
+/// Opcodes indexed by their two-digit code
+#[derive(Debug, Clone)]
+pub struct Table {
+    opcodes: Vec<Option<Opcode>>
+}
+
+impl Table {
+    /// The opcodes defined by the puzzles
+    pub fn standard() -> Table {
+        let mut opcodes = vec![None; 100];
+        for op in STANDARD.iter() {
+            opcodes[op.code as usize] = Some(*op);
+        }
+        Table { opcodes }
+    }
+
+    /// The opcode for an instruction word, ignoring its addressing modes
+    pub fn get(&self, word: i64) -> Option<&Opcode> {
+        if word < 0 {
+            return None;
+        }
+        self.opcodes[(word % 100) as usize].as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Opcode> {
+        self.opcodes.iter().flatten()
+    }
+
+    /// Add an opcode to this table only
+    #[cfg(feature = "std")]
+    pub fn insert(&mut self, opcode: Opcode) -> Result<(), String> {
+        if opcode.code < 1 || opcode.code > 99 {
+            return Err(format!("Opcode {} is not a two-digit code", opcode.code));
+        }
+        if opcode.params.len() > MAX_PARAMS {
+            return Err(format!("Opcode {} takes more than {} parameters", opcode.code, MAX_PARAMS));
+        }
+        if let Some(existing) = self.get(opcode.code) {
+            return Err(format!("Opcode {} is already registered as {}", opcode.code, existing.mnemonic));
+        }
+        self.opcodes[opcode.code as usize] = Some(opcode);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+fn registry() -> &'static RwLock<Arc<Table>> {
+    static REGISTRY: OnceLock<RwLock<Arc<Table>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Arc::new(Table::standard())))
+}
+
+/// The current opcode table. Executors take a snapshot when they start, so
+/// registering an opcode doesn't affect programs already running.
+#[cfg(feature = "std")]
+pub fn table() -> Arc<Table> {
+    registry().read().unwrap().clone()
+}
+
+/// Without `std` there's no registry to share, so only the standard opcodes
+#[cfg(not(feature = "std"))]
+pub fn table() -> Arc<Table> {
+    Arc::new(Table::standard())
+}
+
+/// Add an opcode to the table used by every executor and tool
+#[cfg(feature = "std")]
+pub fn register(opcode: Opcode) -> Result<(), String> {
+    let mut registry = registry().write().unwrap();
+    let mut table = Table::clone(&registry);
+    table.insert(opcode)?;
+    *registry = Arc::new(table);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use super::super::{disassemble, optimize, threaded, Error, Event, Machine, OutputCollector};
+
+    /// The standard opcodes and `sqadd`, kept out of the shared registry
+    fn with_sqadd() -> Table {
+        let mut table = Table::standard();
+        table.insert(Opcode { code: 42, mnemonic: "sqadd", params: &[Read, Read, Write],
+                              semantics: |v| Effect::Store(v[0] * v[0] + v[1]) }).unwrap();
+        table
+    }
+
+    fn outputs(program: &[i64], table: Table) -> Vec<i64> {
+        let mut machine = Machine::new(program.to_vec()).with_table(table);
+        let mut outputs = Vec::new();
+        while let Event::Output(value) = machine.run().unwrap() {
+            outputs.push(value);
+        }
+        outputs
+    }
+
+    #[test]
+    fn custom_opcode() {
+        let program = vec![42,9,10,11, 4,11, 99, 0,0, 7, 2, 0];
+        assert_eq!(outputs(&program, with_sqadd()), vec![51]);
+
+        let mut output = OutputCollector::new();
+        threaded::execute_with_table(&mut program.clone(), &mut &b""[..], &mut output, u64::MAX, with_sqadd()).unwrap();
+        assert_eq!(output.outputs, vec![51]);
+
+        assert_eq!(disassemble::disassemble_with_table(&program, &with_sqadd())[0].text, "sqadd [9], [10], [11]");
+
+        // Constant operands are evaluated ahead of time, like the built-ins
+        let optimized = optimize::optimize_with_table(&program, &with_sqadd());
+        assert_eq!(optimized[0..4], [1101,51,0,11]);
+        assert_eq!(outputs(&optimized, Table::standard()), vec![51]);
+
+        // None of which touched the shared table
+        assert!(table().get(42).is_none());
+    }
+
+    #[test]
+    fn no_write_parameter() {
+        let mut table = Table::standard();
+        table.insert(Opcode { code: 43, mnemonic: "bad", params: &[Read], semantics: |v| Effect::Store(v[0]) }).unwrap();
+        let program = vec![10143, 1, 99];
+
+        let error = Machine::new(program.clone()).with_table(table.clone()).run().unwrap_err();
+        assert_eq!(error.to_string(), "Opcode 43 at 0 has nowhere to store");
+
+        let mut output = OutputCollector::new();
+        let error = threaded::execute_with_table(&mut program.clone(), &mut &b""[..], &mut output, u64::MAX, table).unwrap_err();
+        assert!(matches!(error, Error::NoWriteParameter { pc: 0, code: 43 }));
+    }
+
+    #[test]
+    fn rejects_conflicts() {
+        let op = Opcode { code: 1, mnemonic: "sub", params: &[Read, Read, Write], semantics: |v| Effect::Store(v[0] - v[1]) };
+        assert_eq!(register(op).unwrap_err(), "Opcode 1 is already registered as add");
+
+        let op = Opcode { code: 100, ..op };
+        assert!(register(op).is_err());
+    }
+}
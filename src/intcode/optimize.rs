@@ -0,0 +1,358 @@
+//! Peephole optimisation of intcode program images.
+//!
+//! Every rewrite keeps instructions at the same address and length, so data
+//! addresses baked into the program stay valid. The optimiser first walks
+//! every instruction reachable from address zero. If that walk can't account
+//! for the whole program (indirect jumps, undecodable words, writes into
+//! opcodes or address operands) the image is returned untouched.
+
+use super::{decode, execute, try_decode, AddressingMode, InputProvider, Instruction, OutputCollector, Parameter};
+use super::opcode::{self, Effect, Role, Table};
+
+const ADD_IMMEDIATE: i64 = 1101;
+const JUMP_IMMEDIATE: i64 = 1105;
+const JUMP_ZERO_IMMEDIATE: i64 = 1106;
+
+/// How the words of a reachable instruction are used
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Word {
+    Opcode,
+    Value,
+    Read,
+    Write,
+    Target
+}
+
+struct Analysis {
+    /// Start address of the instruction covering each word, if any
+    start_of: Vec<Option<usize>>,
+    word: Vec<Option<Word>>,
+    read: Vec<bool>,
+    written: Vec<bool>
+}
+
+impl Analysis {
+    /// Whether any word of the instruction at `start` is used as data
+    fn pinned(&self, start: usize) -> bool {
+        self.start_of.iter()
+            .enumerate()
+            .skip(start)
+            .take_while(|(_, s)| **s == Some(start))
+            .any(|(i, _)| self.read[i] || self.written[i])
+    }
+
+    /// A position operand is constant if nothing ever writes to it
+    fn constant(&self, program: &[i64], param: &Parameter) -> Option<i64> {
+        match param.mode {
+            AddressingMode::Immediate => Some(param.value),
+            AddressingMode::Position => {
+                let address = param.value as usize;
+                if self.written[address] { None } else { Some(program[address]) }
+            },
+            AddressingMode::Relative => None
+        }
+    }
+}
+
+/// Values of the instruction's read and jump parameters, if all are known
+fn constants<F>(instruction: &Instruction, mut constant: F) -> Option<Vec<i64>>
+    where F: FnMut(&Parameter) -> Option<i64>
+{
+    instruction.params.iter()
+        .zip(instruction.opcode.params)
+        .filter(|(_, role)| **role != Role::Write)
+        .map(|(param, _)| constant(param))
+        .collect()
+}
+
+fn analyse(program: &[i64], table: &Table) -> Option<Analysis> {
+    let len = program.len();
+    let mut start_of = vec![None; len];
+    let mut word = vec![None; len];
+    let mut read = vec![false; len];
+    let mut written = vec![false; len];
+
+    let in_range = |p: &Parameter| p.value >= 0 && (p.value as usize) < len;
+
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        if pc >= len || start_of[pc] == Some(pc) {
+            continue;
+        }
+
+        let instruction = try_decode(program, pc, table)?;
+        let mut words = vec![Word::Opcode];
+        let mut targets = Vec::new();
+
+        for (param, role) in instruction.params.iter().zip(instruction.opcode.params) {
+            match (role, &param.mode) {
+                // The relative base isn't tracked, so these could touch anything
+                (_, AddressingMode::Relative) => return None,
+                (Role::Read, AddressingMode::Immediate) => words.push(Word::Value),
+                (Role::Read, AddressingMode::Position) => {
+                    if !in_range(param) {
+                        return None;
+                    }
+                    read[param.value as usize] = true;
+                    words.push(Word::Read);
+                },
+                (Role::Write, _) => {
+                    if !in_range(param) {
+                        return None;
+                    }
+                    written[param.value as usize] = true;
+                    words.push(Word::Write);
+                },
+                // Indirect jumps could land anywhere
+                (Role::Jump, AddressingMode::Immediate) if param.value >= 0 => {
+                    targets.push(param.value as usize);
+                    words.push(Word::Target);
+                },
+                (Role::Jump, _) => return None
+            };
+        }
+
+        let next = pc + instruction.opcode.length();
+        let immediate = |p: &Parameter| match p.mode {
+            AddressingMode::Immediate => Some(p.value),
+            _ => None
+        };
+
+        let successors = match constants(&instruction, immediate) {
+            Some(values) => match (instruction.opcode.semantics)(&values) {
+                Effect::Halt => vec![],
+                Effect::Jump(target) => vec![target as usize],
+                _ => vec![next]
+            },
+            None => {
+                targets.push(next);
+                targets
+            }
+        };
+
+        for (offset, w) in words.into_iter().enumerate() {
+            let address = pc + offset;
+            // Instructions decoded at overlapping offsets
+            if start_of[address].is_some() {
+                return None;
+            }
+            start_of[address] = Some(pc);
+            word[address] = Some(w);
+        }
+
+        pending.extend(successors);
+    }
+
+    // Writing an immediate operand only changes the value it supplies, which
+    // pinning that instruction accounts for. Any other write into code could
+    // change what executes, so the walk above can't be trusted.
+    let unsound = written.iter()
+        .zip(&word)
+        .any(|(w, r)| *w && r.is_some() && *r != Some(Word::Value));
+
+    if unsound { None } else { Some(Analysis { start_of, word, read, written }) }
+}
+
+/// Target of an unconditional jump in the rewritten image
+fn unconditional_target(analysis: &Analysis, image: &[i64], address: usize) -> Option<usize> {
+    if address >= image.len() || analysis.start_of[address] != Some(address) || analysis.pinned(address) {
+        return None;
+    }
+
+    let taken = match image[address] {
+        JUMP_IMMEDIATE => image[address + 1] != 0,
+        JUMP_ZERO_IMMEDIATE => image[address + 1] == 0,
+        _ => false
+    };
+
+    if taken { Some(image[address + 2] as usize) } else { None }
+}
+
+/// Rewrite `program` into an equivalent image that executes fewer
+/// instructions. Instructions whose inputs are all constant are evaluated
+/// ahead of time: stores become an immediate add, and jumps become
+/// unconditional jumps (or jumps to the next instruction). Jump chains are
+/// then threaded to their final destination. Instructions whose words are
+/// ever read or written as data are left alone.
+pub fn optimize(program: &[i64]) -> Vec<i64> {
+    optimize_with_table(program, &opcode::table())
+}
+
+/// `optimize` with `table` rather than the registered opcodes
+pub fn optimize_with_table(program: &[i64], table: &Table) -> Vec<i64> {
+    let mut image = program.to_vec();
+
+    let analysis = match analyse(program, table) {
+        Some(a) => a,
+        None => return image
+    };
+
+    let starts: Vec<usize> = (0..program.len())
+        .filter(|&i| analysis.start_of[i] == Some(i) && !analysis.pinned(i))
+        .collect();
+
+    for &pc in &starts {
+        let instruction = decode(program, pc, table).unwrap();
+        let length = instruction.opcode.length();
+
+        let values = match constants(&instruction, |p| analysis.constant(program, p)) {
+            Some(values) => values,
+            None => continue
+        };
+
+        let write = instruction.params.iter()
+            .zip(instruction.opcode.params)
+            .find(|(_, role)| **role == Role::Write)
+            .map(|(param, _)| param.value);
+
+        match ((instruction.opcode.semantics)(&values), length, write) {
+            (Effect::Store(v), 4, Some(o)) => {
+                image[pc..pc + 4].copy_from_slice(&[ADD_IMMEDIATE, v, 0, o]);
+            },
+            (Effect::Jump(target), 3, _) => {
+                image[pc..pc + 3].copy_from_slice(&[JUMP_IMMEDIATE, 1, target]);
+            },
+            (Effect::Next, 3, None) => {
+                image[pc..pc + 3].copy_from_slice(&[JUMP_IMMEDIATE, 1, pc as i64 + 3]);
+            },
+            _ => {}
+        };
+    }
+
+    for &pc in &starts {
+        let targets = (pc..program.len())
+            .take_while(|&i| analysis.start_of[i] == Some(pc))
+            .filter(|&i| analysis.word[i] == Some(Word::Target) || image[pc] == JUMP_IMMEDIATE && i == pc + 2);
+
+        for address in targets.collect::<Vec<usize>>() {
+            let mut target = image[address] as usize;
+            let mut visited = vec![pc];
+            while let Some(next) = unconditional_target(&analysis, &image, target) {
+                if visited.contains(&target) {
+                    break;
+                }
+                visited.push(target);
+                target = next;
+            }
+
+            image[address] = target as i64;
+        }
+    }
+
+    image
+}
+
+/// The first sample on which two programs produced different outputs, or
+/// on which only one of them failed
+#[derive(Debug, PartialEq)]
+pub struct Divergence {
+    pub inputs: Vec<i64>,
+    pub expected: Vec<i64>,
+    pub actual: Vec<i64>,
+    /// How each program failed, if it did
+    pub expected_error: Option<String>,
+    pub actual_error: Option<String>
+}
+
+fn outputs(program: &[i64], inputs: &[i64]) -> (Vec<i64>, Option<String>) {
+    let mut program = program.to_vec();
+    let mut input = InputProvider::new(inputs.to_vec());
+    let mut output = OutputCollector::new();
+    let result = execute(&mut program, &mut input, &mut output);
+    (output.outputs, result.err().map(|e| e.to_string()))
+}
+
+/// Run both programs on every sample input and compare their outputs
+pub fn check(original: &[i64], optimized: &[i64], samples: &[Vec<i64>]) -> Result<(), Divergence> {
+    for inputs in samples {
+        let (expected, expected_error) = outputs(original, inputs);
+        let (actual, actual_error) = outputs(optimized, inputs);
+
+        if expected != actual || expected_error.is_some() != actual_error.is_some() {
+            return Err(Divergence { inputs: inputs.clone(), expected, actual, expected_error, actual_error });
+        }
+    }
+
+    Ok(())
+}
+
+/// Optimise `program`, proving the result against the sample inputs
+pub fn optimize_checked(program: &[i64], samples: &[Vec<i64>]) -> Result<Vec<i64>, Divergence> {
+    let optimized = optimize(program);
+    check(program, &optimized, samples).map(|_| optimized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_immediate_arithmetic() {
+        let program = vec![1102,6,7,9, 1107,1,2,10, 99, 0, 0];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[0..4], [1101,42,0,9]);
+        assert_eq!(optimized[4..8], [1101,1,0,10]);
+    }
+
+    #[test]
+    fn folds_reads_of_unwritten_cells() {
+        let program = vec![1,7,8,9, 4,9,99, 20,22, 0];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[0..4], [1101,42,0,9]);
+    }
+
+    #[test]
+    fn constant_jumps() {
+        // Taken: jump-if-zero on an immediate zero
+        let program = vec![1106,0,5, 104,7, 104,8,99];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[0..3], [1105,1,5]);
+
+        // Never taken: becomes a jump to the next instruction
+        let program = vec![1105,0,9, 104,1,99];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[0..3], [1105,1,3]);
+    }
+
+    #[test]
+    fn threads_jump_chains() {
+        let program = vec![1105,1,6, 99, 0,0, 1105,1,9, 104,5, 99];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[2], 9);
+
+        // A conditional jump keeps its condition but skips the chain
+        let program = vec![3,20, 1005,20,9, 104,0, 99, 0, 1105,1,12, 104,1, 99,
+                           0,0,0,0,0,0];
+        let optimized = optimize_checked(&program, &[vec![0], vec![1]]).unwrap();
+        assert_eq!(optimized[2..5], [1005,20,12]);
+    }
+
+    #[test]
+    fn skips_no_ops() {
+        let program = vec![1105,1,3, 1106,1,0, 104,3, 99];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[2], 6);
+    }
+
+    #[test]
+    fn leaves_written_instructions() {
+        // The first add writes over an operand of the second
+        let program = vec![1101,4,4,5, 1101,2,3,12, 4,12, 99, 0, 0];
+        let optimized = optimize_checked(&program, &[vec![]]).unwrap();
+        assert_eq!(optimized[0..4], [1101,8,0,5]);
+        assert_eq!(optimized[4..8], program[4..8]);
+    }
+
+    #[test]
+    fn leaves_self_modifying_programs() {
+        let program = super::super::parse_input(include_str!("../../input/5.txt")).unwrap();
+        assert_eq!(optimize(&program), program);
+    }
+
+    #[test]
+    fn reports_divergence() {
+        let divergence = check(&[104,1,99], &[104,2,99], &[vec![]]).unwrap_err();
+        assert_eq!(divergence.expected, vec![1]);
+        assert_eq!(divergence.actual, vec![2]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Parsing of intcode program text.
+//!
+//! Words are separated by commas or newlines, and whitespace around them is
+//! ignored. A `#` starts a comment running to the end of the line. A comma
+//! may end a line, but empty words are otherwise an error. Blank lines
+//! separate programs, so one file can hold several.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// Byte offset of the offending text in the input
+    pub offset: usize,
+    /// Which program in the input the error is in
+    pub program: usize,
+    /// Index of the offending word within its program, i.e. its address
+    pub index: usize,
+    pub message: String
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "byte {} (program {}, word {}): {}", self.offset, self.program, self.index, self.message)
+    }
+}
+
+/// Parse every program in `input`, with the byte offset each starts at
+fn programs(input: &str) -> Result<Vec<(usize, Vec<i64>)>, ParseError> {
+    let mut programs = Vec::new();
+    let mut current: Option<(usize, Vec<i64>)> = None;
+
+    let mut line_start = 0;
+    for line in input.split('\n') {
+        let start = line_start;
+        line_start += line.len() + 1;
+
+        if line.trim().is_empty() {
+            programs.extend(current.take());
+            continue;
+        }
+
+        let code = match line.find('#') {
+            Some(i) => &line[..i],
+            None => line
+        };
+
+        let pieces: Vec<&str> = code.split(',').collect();
+        let mut piece_start = start;
+
+        for (i, piece) in pieces.iter().enumerate() {
+            let offset = piece_start + (piece.len() - piece.trim_start().len());
+            piece_start += piece.len() + 1;
+
+            let word = piece.trim();
+            let index = current.as_ref().map_or(0, |(_, words)| words.len());
+            let error = |message: String| ParseError { offset, program: programs.len(), index, message };
+
+            if word.is_empty() {
+                let comment_only = pieces.len() == 1;
+                let trailing_comma = i > 0 && i == pieces.len() - 1;
+                if comment_only || trailing_comma {
+                    continue;
+                }
+                return Err(error(String::from("Empty word")));
+            }
+
+            match word.parse::<i64>() {
+                Ok(n) => current.get_or_insert_with(|| (offset, Vec::new())).1.push(n),
+                Err(_) => return Err(error(format!("Invalid word `{}`", word)))
+            };
+        }
+    }
+
+    programs.extend(current);
+
+    Ok(programs)
+}
+
+/// Parse a single intcode program
+pub fn parse_input(input: &str) -> Result<Vec<i64>, ParseError> {
+    let mut programs = programs(input)?;
+
+    match programs.len() {
+        1 => Ok(programs.remove(0).1),
+        0 => Err(ParseError { offset: input.len(), program: 0, index: 0, message: String::from("No program found") }),
+        n => Err(ParseError {
+            offset: programs[1].0,
+            program: 1,
+            index: 0,
+            message: format!("Expected one program, found {}", n)
+        })
+    }
+}
+
+/// Parse every program in a file of blank-line separated programs
+pub fn parse_programs(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    Ok(programs(input)?.into_iter().map(|(_, words)| words).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separators() {
+        assert_eq!(parse_input("1,0,0,3,99"), Ok(vec![1, 0, 0, 3, 99]));
+        assert_eq!(parse_input("1,0,0,3,99\n"), Ok(vec![1, 0, 0, 3, 99]));
+        assert_eq!(parse_input(" 1, 0 ,0,\r\n3\n99 \r\n\n"), Ok(vec![1, 0, 0, 3, 99]));
+        assert_eq!(parse_input("1,-2,+3"), Ok(vec![1, -2, 3]));
+    }
+
+    #[test]
+    fn comments() {
+        let text = "# header\n1,0,0,3, # add\n99 # halt\n# trailer\n";
+        assert_eq!(parse_input(text), Ok(vec![1, 0, 0, 3, 99]));
+    }
+
+    #[test]
+    fn several_programs() {
+        let text = "# first\n104,1,99\n\n\n# second\n104,2,\n99\n";
+        assert_eq!(parse_programs(text), Ok(vec![vec![104, 1, 99], vec![104, 2, 99]]));
+
+        let error = parse_input(text).unwrap_err();
+        assert_eq!((error.offset, error.program), (28, 1));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_input("1,0,x,3"),
+                   Err(ParseError { offset: 4, program: 0, index: 2, message: String::from("Invalid word `x`") }));
+        assert_eq!(parse_input("1,2\n3,,4"),
+                   Err(ParseError { offset: 6, program: 0, index: 3, message: String::from("Empty word") }));
+        assert_eq!(parse_input("99\n\n1,0;0").unwrap_err().program, 1);
+        assert_eq!(parse_input(" \n# nothing\n").unwrap_err().message, "No program found");
+    }
+}
@@ -0,0 +1,281 @@
+//! Serving an intcode program over TCP.
+//!
+//! Every connection gets a fresh machine running the program. Input is read
+//! from the connection a line at a time as the program asks for it, and
+//! output is written back as it's produced, so the machine can be scripted
+//! from anything that speaks TCP, e.g. `nc localhost <port>`.
+//!
+//! In integer mode each line in or out is one value. In ASCII mode a line of
+//! input is fed to the program character by character, newline included, and
+//! outputs below 128 are written as characters, anything else as a number on
+//! its own line. A session ends when the program halts, fails or runs out of
+//! its instruction budget; errors are reported as a line starting `error:`.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+use super::{Error, Event, Image, Machine};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Integers,
+    Ascii
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub mode: Mode,
+    /// Instructions a session may execute before it's cut off
+    pub budget: u64
+}
+
+/// Read a line of input into `machine`, returning false at the end of input
+fn feed<I: BufRead>(machine: &mut Machine, mode: Mode, input: &mut I) -> Result<bool, Error> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(false);
+    }
+
+    match mode {
+        Mode::Integers => {
+            let text = line.trim();
+            let value = text.parse().map_err(|_| Error::BadInput { pc: machine.pc(), text: String::from(text) })?;
+            machine.push_input(value);
+        },
+        Mode::Ascii => {
+            if !line.ends_with('\n') {
+                line.push('\n');
+            }
+            for byte in line.bytes().filter(|b| *b != b'\r') {
+                machine.push_input(byte as i64);
+            }
+        }
+    };
+    Ok(true)
+}
+
+fn emit<O: Write>(mode: Mode, output: &mut O, value: i64) -> io::Result<()> {
+    match mode {
+        Mode::Ascii if (0..128).contains(&value) => output.write_all(&[value as u8]),
+        _ => writeln!(output, "{}", value)
+    }
+}
+
+fn converse<I,O>(machine: &mut Machine, mode: Mode, input: &mut I, output: &mut O) -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    loop {
+        match machine.run()? {
+            Event::Input => {
+                // The client may be waiting on output before it answers
+                output.flush()?;
+                if !feed(machine, mode, input)? {
+                    return Err(Error::InputExhausted { pc: machine.pc() });
+                }
+            },
+            Event::Output(value) => emit(mode, output, value)?,
+            Event::Pause => {},
+            Event::Halt => return Ok(())
+        };
+    }
+}
+
+/// Run one session of `machine` against a client, reporting any error to
+/// the client as well as returning it
+pub fn session<I,O>(machine: &mut Machine, mode: Mode, input: &mut I, output: &mut O) -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    let result = converse(machine, mode, input, output);
+    if let Err(e) = &result {
+        // The client may already have gone, in which case there's no one to tell
+        if mode == Mode::Ascii {
+            let _ = writeln!(output);
+        }
+        let _ = writeln!(output, "error: {}", e);
+    }
+    let _ = output.flush();
+    result
+}
+
+/// Open connections, so stopping can close them
+type Sessions = Arc<Mutex<HashMap<u64, TcpStream>>>;
+
+/// Stops a running `Server` from another thread
+#[derive(Clone)]
+pub struct Stopper {
+    address: SocketAddr,
+    stopping: Arc<AtomicBool>,
+    sessions: Sessions
+}
+
+impl Stopper {
+    /// Stop accepting connections and close the ones open, which ends their
+    /// sessions as if the clients had stopped sending
+    pub fn stop(&self) {
+        // Under the sessions lock, so a connection accepted meanwhile either
+        // is shut down here or sees that we're stopping
+        let sessions = self.sessions.lock().unwrap();
+        self.stopping.store(true, Ordering::SeqCst);
+        for stream in sessions.values() {
+            let _ = stream.shutdown(Shutdown::Read);
+        }
+        drop(sessions);
+
+        // Wake the listener, which is blocked waiting for a connection
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    image: Arc<Image>,
+    config: Config,
+    stopper: Stopper
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(address: A, image: Image, config: Config) -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        let stopper = Stopper {
+            address: listener.local_addr()?,
+            stopping: Arc::new(AtomicBool::new(false)),
+            sessions: Arc::new(Mutex::new(HashMap::new()))
+        };
+        Ok(Server { listener, image: Arc::new(image), config, stopper })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.stopper.address
+    }
+
+    pub fn stopper(&self) -> Stopper {
+        self.stopper.clone()
+    }
+
+    /// Serve connections until stopped, then wait for the open sessions to
+    /// finish. Returns the number of sessions served.
+    pub fn run(self) -> io::Result<usize> {
+        let mut threads: Vec<JoinHandle<()>> = Vec::new();
+        let mut served = 0;
+
+        for (id, stream) in self.listener.incoming().enumerate() {
+            let stream = stream?;
+            let id = id as u64;
+            {
+                let mut sessions = self.stopper.sessions.lock().unwrap();
+                if self.stopper.stopping.load(Ordering::SeqCst) {
+                    break;
+                }
+                sessions.insert(id, stream.try_clone()?);
+            }
+
+            // Only sessions still running need waiting for
+            threads.retain(|thread| !thread.is_finished());
+            served += 1;
+
+            let image = self.image.clone();
+            let Config { mode, budget } = self.config;
+            let sessions = self.stopper.sessions.clone();
+            threads.push(thread::spawn(move || {
+                let mut machine = Machine::new(image.words.clone()).starting_at(image.entry).with_limit(budget);
+                if let Ok(reader) = stream.try_clone() {
+                    let _ = session(&mut machine, mode, &mut BufReader::new(reader), &mut BufWriter::new(&stream));
+                }
+                sessions.lock().unwrap().remove(&id);
+            }));
+        }
+
+        for thread in threads {
+            let _ = thread.join();
+        }
+        Ok(served)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    /// Add one to every input until a zero
+    const INCREMENT: [i64; 16] = [3,15, 1006,15,14, 1001,15,1,15, 4,15, 1105,1,0, 99, 0];
+
+    fn talk(program: &[i64], mode: Mode, budget: u64, input: &str) -> (String, Result<(), Error>) {
+        let mut machine = Machine::new(program.to_vec()).with_limit(budget);
+        let mut output = Vec::new();
+        let result = session(&mut machine, mode, &mut input.as_bytes(), &mut output);
+        (String::from_utf8(output).unwrap(), result)
+    }
+
+    #[test]
+    fn integer_sessions() {
+        let program = [3,9, 1001,9,1,9, 4,9, 99, 0];
+        assert_eq!(talk(&program, Mode::Integers, 100, "41\n").0, "42\n");
+        assert_eq!(talk(&program, Mode::Integers, 100, "").0, "error: Input exhausted at 0\n");
+        assert_eq!(talk(&program, Mode::Integers, 100, "x\n").0, "error: Bad input `x` at 0\n");
+    }
+
+    #[test]
+    fn ascii_sessions() {
+        // Echo characters back, shifted by one
+        let program = [3,20, 1001,20,1,20, 4,20, 1105,1,0];
+        let (output, result) = talk(&program, Mode::Ascii, 1000, "HAL\r\n");
+        assert_eq!(output, "IBM\u{b}\nerror: Input exhausted at 0\n");
+        assert!(result.is_err());
+
+        // Large values are written as numbers
+        assert_eq!(talk(&[104,1000, 104,65, 99], Mode::Ascii, 10, "").0, "1000\nA");
+    }
+
+    #[test]
+    fn budget() {
+        let (output, result) = talk(&[1105,1,0], Mode::Integers, 50, "");
+        assert_eq!(output, "error: No halt after 50 steps\n");
+        assert!(matches!(result, Err(Error::StepLimit(50))));
+    }
+
+    #[test]
+    fn serves_connections() {
+        let config = Config { mode: Mode::Integers, budget: 1000 };
+        let server = Server::bind("127.0.0.1:0", Image::new(INCREMENT.to_vec()), config).unwrap();
+        let address = server.local_addr();
+        let stopper = server.stopper();
+        let running = thread::spawn(move || server.run().unwrap());
+
+        let mut finished = TcpStream::connect(address).unwrap();
+        finished.write_all(b"1\n2\n0\n").unwrap();
+        let mut output = String::new();
+        finished.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "2\n3\n");
+
+        // Stopping ends sessions still waiting on input
+        let mut open = TcpStream::connect(address).unwrap();
+        open.write_all(b"7\n").unwrap();
+        let mut line = [0; 2];
+        open.read_exact(&mut line).unwrap();
+        assert_eq!(&line, b"8\n");
+
+        stopper.stop();
+        assert_eq!(running.join().unwrap(), 2);
+        output.clear();
+        open.read_to_string(&mut output).unwrap();
+        assert_eq!(output, "error: Input exhausted at 0\n");
+    }
+
+    #[test]
+    fn connections_after_stopping() {
+        let config = Config { mode: Mode::Integers, budget: 1000 };
+        let server = Server::bind("127.0.0.1:0", Image::new(INCREMENT.to_vec()), config).unwrap();
+        server.stopper().stop();
+
+        // Queued behind the wake-up, so never served
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        assert_eq!(server.run().unwrap(), 0);
+        let mut output = String::new();
+        assert!(client.read_to_string(&mut output).is_err() || output.is_empty());
+    }
+}
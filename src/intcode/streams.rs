@@ -0,0 +1,149 @@
+//! Running programs against `std::io` streams: each input is read as a
+//! line holding one number, and each output written as a number.
+
+use std::io::{self, BufRead, Read, Write};
+use std::sync::Arc;
+
+use super::{Error, Event, Machine, Registers};
+use super::opcode::{self, Table};
+
+pub struct InputProvider {
+    pub inputs: String
+}
+
+impl InputProvider {
+    pub fn new(inputs: Vec<i64>) -> InputProvider {
+        InputProvider { inputs: inputs.iter().map(|i| format!("{}\n", i)).collect() }
+    }
+}
+
+impl Read for InputProvider {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.inputs.is_empty() {
+            Ok(0)
+        }
+        else {
+            let mut bytes: usize = 0;
+            for i in 0..std::cmp::min(buf.len(), self.inputs.len()) {
+                let c = self.inputs.remove(0);
+                buf[i] = c as u8;
+                bytes += 1;
+            }
+            Ok(bytes)
+        }
+    }
+}
+
+impl BufRead for InputProvider {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.inputs.as_bytes())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        for _ in 0..amt {
+            self.inputs.remove(0);
+        }
+    }
+}
+
+pub struct OutputCollector {
+    pub outputs: Vec<i64>
+}
+
+impl Default for OutputCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputCollector {
+    pub fn new() -> OutputCollector {
+        OutputCollector { outputs: Vec::new() }
+    }
+}
+
+impl Write for OutputCollector {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match std::str::from_utf8(buf) {
+            Ok(s) => {
+                match s.trim().parse::<i64>() {
+                    Ok(n) => { 
+                        self.outputs.push(n);
+                        Ok(buf.len())
+                    }
+                    Err(_) => Err(io::Error::other("Parse error"))
+                }
+            },
+            Err(_) => Err(io::Error::other("Parse error"))
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+
+pub(super) fn read_value<I: BufRead>(is: &mut I, pc: usize) -> Result<i64, Error> {
+    let mut input_data = String::new();
+    if is.read_line(&mut input_data)? == 0 {
+        return Err(Error::InputExhausted { pc });
+    }
+    let text = input_data.trim();
+    text.parse::<i64>().map_err(|_| Error::BadInput { pc, text: String::from(text) })
+}
+
+pub(super) fn write_value<O: Write>(os: &mut O, value: i64) -> Result<(), Error> {
+    // A single write per value, so collectors never see a lone sign
+    os.write_all(value.to_string().as_bytes())?;
+    os.flush()?;
+    Ok(())
+}
+
+
+/// Run `machine` to completion, reading input from `input` as it's needed
+fn drive<I,O>(machine: &mut Machine, input: &mut I, output: &mut O) -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    loop {
+        match machine.run()? {
+            Event::Input => {
+                let value = read_value(input, machine.pc())?;
+                machine.push_input(value);
+            },
+            Event::Output(value) => write_value(output, value)?,
+            Event::Pause => {},
+            Event::Halt => return Ok(())
+        };
+    }
+}
+
+
+/// Execute `program` in place. Memory grows as the program writes past its end.
+pub fn execute<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O) -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    execute_limited(program, input, output, u64::MAX).map(|_| ())
+}
+
+/// Execute `program` in place, giving up with `Error::StepLimit` rather than
+/// run more than `limit` instructions. Returns the number executed.
+pub fn execute_limited<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O, limit: u64) -> Result<u64, Error> where
+    I: BufRead, O: Write
+{
+    execute_from(program, Registers { pc: 0, base: 0, steps: 0 }, limit, opcode::table(), input, output)
+}
+
+/// Interpret `program` from the given registers, e.g. to take over from
+/// another backend
+pub(super) fn execute_from<I,O>(program: &mut Vec<i64>, registers: Registers, limit: u64, table: Arc<Table>, input: &mut I, output: &mut O)
+    -> Result<u64, Error> where
+    I: BufRead, O: Write
+{
+    let mut machine = Machine::resume(std::mem::take(program), registers, limit, table);
+    let result = drive(&mut machine, input, output);
+    let steps = machine.steps();
+
+    *program = machine.into_memory();
+    result.map(|_| steps)
+}
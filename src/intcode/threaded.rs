@@ -0,0 +1,268 @@
+//! Closure-compiled intcode backend.
+//!
+//! Each instruction is decoded once, the first time execution reaches it, into
+//! a closure with its opcode and addressing modes already resolved. The dispatch table is
+//! indexed by address, so loops run without decoding. A write into any word of
+//! a compiled instruction invalidates the table; execution then continues in
+//! the interpreter from the next instruction.
+
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+
+use super::{address, decode, execute_from, read_value, write_value, AddressingMode, Error, Parameter, Registers};
+use super::opcode::{self, Effect, Role, Table, MAX_PARAMS};
+
+/// What the dispatch loop does after an instruction
+enum Step {
+    Next(usize),
+    Input(usize, usize),
+    Output(i64, usize),
+    /// The instruction wrote over compiled code
+    Modified(usize),
+    Halt
+}
+
+struct Memory<'a> {
+    words: &'a mut Vec<i64>,
+    code: Vec<bool>,
+    base: i64
+}
+
+impl<'a> Memory<'a> {
+    fn load(&self, address: usize) -> i64 {
+        self.words.get(address).copied().unwrap_or(0)
+    }
+
+    /// Store `value`, returning whether it landed on compiled code
+    fn store(&mut self, address: usize, value: i64) -> bool {
+        super::store(self.words, address, value);
+        if self.code.len() < self.words.len() {
+            self.code.resize(self.words.len(), false);
+        }
+        self.code[address]
+    }
+}
+
+type Op = Box<dyn Fn(&mut Memory) -> Result<Step, Error>>;
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Immediate(i64),
+    Position(i64),
+    Relative(i64)
+}
+
+impl Operand {
+    fn new(param: &Parameter) -> Operand {
+        match param.mode {
+            AddressingMode::Immediate => Operand::Immediate(param.value),
+            AddressingMode::Position => Operand::Position(param.value),
+            AddressingMode::Relative => Operand::Relative(param.value)
+        }
+    }
+
+    #[inline]
+    fn load(self, m: &Memory, pc: usize) -> Result<i64, Error> {
+        match self {
+            Operand::Immediate(v) => Ok(v),
+            Operand::Position(a) => address(a, pc).map(|a| m.load(a)),
+            Operand::Relative(offset) => address(m.base.saturating_add(offset), pc).map(|a| m.load(a))
+        }
+    }
+
+    /// The address a write parameter stores to
+    #[inline]
+    fn address(self, m: &Memory, pc: usize) -> Result<usize, Error> {
+        match self {
+            Operand::Immediate(a) | Operand::Position(a) => address(a, pc),
+            Operand::Relative(offset) => address(m.base.saturating_add(offset), pc)
+        }
+    }
+}
+
+/// Compile the instruction at `pc`, returning it with its length
+fn compile(words: &[i64], pc: usize, table: &Table) -> Result<(Op, usize), Error> {
+    let instruction = decode(words, pc, table)?;
+    let opcode = instruction.opcode;
+    let next = pc + opcode.length();
+
+    let mut operands = [Operand::Immediate(0); MAX_PARAMS];
+    let mut count = 0;
+    let mut write = None;
+
+    for (param, role) in instruction.params.iter().zip(opcode.params) {
+        match role {
+            Role::Read | Role::Jump => {
+                operands[count] = Operand::new(param);
+                count += 1;
+            },
+            Role::Write => {
+                write = write.or(Some(Operand::new(param)));
+            }
+        };
+    }
+
+    let write = move |m: &Memory| match write {
+        Some(operand) => operand.address(m, pc),
+        None => Err(Error::NoWriteParameter { pc, code: opcode.code })
+    };
+
+    let op = Box::new(move |m: &mut Memory| {
+        let mut values = [0; MAX_PARAMS];
+        for (value, operand) in values.iter_mut().zip(&operands[..count]) {
+            *value = operand.load(m, pc)?;
+        }
+
+        Ok(match (opcode.semantics)(&values[..count]) {
+            Effect::Store(v) => {
+                let address = write(m)?;
+                if m.store(address, v) { Step::Modified(next) } else { Step::Next(next) }
+            },
+            Effect::Input => Step::Input(write(m)?, next),
+            Effect::Output(v) => Step::Output(v, next),
+            Effect::Jump(target) => Step::Next(address(target, pc)?),
+            Effect::AdjustBase(offset) => {
+                m.base = m.base.wrapping_add(offset);
+                Step::Next(next)
+            },
+            Effect::Next => Step::Next(next),
+            Effect::Halt => Step::Halt
+        })
+    });
+
+    Ok((op, opcode.length()))
+}
+
+/// Execute `program` with the closure-compiled backend. Behaves exactly like
+/// `intcode::execute`, falling back to it if the program modifies its own code.
+pub fn execute<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O) -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    execute_limited(program, input, output, u64::MAX).map(|_| ())
+}
+
+/// The closure-compiled counterpart of `intcode::execute_limited`
+pub fn execute_limited<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O, limit: u64) -> Result<u64, Error> where
+    I: BufRead, O: Write
+{
+    run(program, input, output, limit, opcode::table())
+}
+
+/// `execute_limited` decoding with `table` rather than the registered opcodes
+pub fn execute_with_table<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O, limit: u64, table: Table) -> Result<u64, Error> where
+    I: BufRead, O: Write
+{
+    run(program, input, output, limit, Arc::new(table))
+}
+
+fn run<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O, limit: u64, table: Arc<Table>) -> Result<u64, Error> where
+    I: BufRead, O: Write
+{
+    let len = program.len();
+    let mut ops: Vec<Option<Op>> = (0..len).map(|_| None).collect();
+    let mut memory = Memory { words: program, code: vec![false; len], base: 0 };
+
+    let mut pc = 0;
+    let mut steps = 0;
+    while pc < memory.words.len() {
+        let registers = Registers { pc, base: memory.base, steps };
+
+        // Code the program wrote past its original end is left to the interpreter
+        if pc >= len {
+            return execute_from(memory.words, registers, limit, table, input, output);
+        }
+        if steps >= limit {
+            return Err(Error::StepLimit(limit));
+        }
+
+        if ops[pc].is_none() {
+            let (op, length) = compile(memory.words, pc, &table)?;
+            for word in &mut memory.code[pc..pc + length] {
+                *word = true;
+            }
+            ops[pc] = Some(op);
+        }
+
+        let op = ops[pc].as_ref().unwrap();
+        steps += 1;
+        pc = match op(&mut memory)? {
+            Step::Next(next) => next,
+            Step::Input(address, next) => {
+                let value = read_value(input, pc)?;
+                if memory.store(address, value) {
+                    let registers = Registers { pc: next, base: memory.base, steps };
+                    return execute_from(memory.words, registers, limit, table, input, output);
+                }
+                next
+            },
+            Step::Output(value, next) => {
+                write_value(output, value)?;
+                next
+            },
+            Step::Modified(next) => {
+                let registers = Registers { pc: next, base: memory.base, steps };
+                return execute_from(memory.words, registers, limit, table, input, output);
+            },
+            Step::Halt => break
+        };
+    }
+
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{compiler, parse_input, InputProvider, OutputCollector};
+
+    /// Run both backends, checking they agree on outputs and final memory
+    fn agree(program: &[i64], inputs: Vec<i64>) -> Vec<i64> {
+        let mut interpreted = program.to_vec();
+        let mut input = InputProvider::new(inputs.clone());
+        let mut expected = OutputCollector::new();
+        super::super::execute(&mut interpreted, &mut input, &mut expected).unwrap();
+
+        let mut compiled = program.to_vec();
+        let mut input = InputProvider::new(inputs);
+        let mut actual = OutputCollector::new();
+        execute(&mut compiled, &mut input, &mut actual).unwrap();
+
+        assert_eq!(expected.outputs, actual.outputs);
+        assert_eq!(interpreted, compiled);
+        actual.outputs
+    }
+
+    #[test]
+    fn day2() {
+        let mut program = parse_input(include_str!("../../input/2.txt")).unwrap();
+        program[1] = 12;
+        program[2] = 2;
+        agree(&program, vec![]);
+    }
+
+    #[test]
+    fn day5() {
+        // Day 5 patches one of its own opcodes before first reaching it
+        let program = parse_input(include_str!("../../input/5.txt")).unwrap();
+        assert_eq!(agree(&program, vec![5]), vec![9168267]);
+    }
+
+    #[test]
+    fn day7() {
+        let program = parse_input(include_str!("../../input/7.txt")).unwrap();
+        agree(&program, vec![3, 0]);
+    }
+
+    #[test]
+    fn loops() {
+        let program = compiler::compile("n = input; t = 0; while n > 0 { t = t + n * n; n = n - 1; } output t;").unwrap();
+        assert_eq!(agree(&program, vec![100]), vec![338350]);
+    }
+
+    #[test]
+    fn self_modifying_loop() {
+        // Increments the immediate operand of the output instruction each pass
+        let program = vec![104,0, 1001,1,1,1, 1007,1,3,14, 1005,14,0, 99, 0];
+        assert_eq!(agree(&program, vec![]), vec![0, 1, 2]);
+    }
+}
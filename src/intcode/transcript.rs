@@ -0,0 +1,242 @@
+//! Transcripts of a program's input and output.
+//!
+//! A transcript lists every value a program read or wrote, in order, tagged
+//! with the step it happened at: the number of instructions executed up to
+//! and including the one doing the IO. Replaying a transcript feeds the
+//! recorded inputs back and checks the program writes the same outputs at
+//! the same steps, so a recorded interactive session becomes a regression
+//! test.
+//!
+//! The text form has one entry per line, and `#` starts a comment:
+//!
+//! ```text
+//! 1 in 41
+//! 3 out 42
+//! 4 halt
+//! ```
+
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use super::{read_value, write_value, Error, Event, Machine};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entry {
+    Input { step: u64, value: i64 },
+    Output { step: u64, value: i64 },
+    Halt { step: u64 }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Entry::Input { step, value } => write!(f, "{} in {}", step, value),
+            Entry::Output { step, value } => write!(f, "{} out {}", step, value),
+            Entry::Halt { step } => write!(f, "{} halt", step)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub entries: Vec<Entry>
+}
+
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+impl Transcript {
+    pub fn new() -> Transcript {
+        Transcript { entries: Vec::new() }
+    }
+
+    pub fn parse(text: &str) -> Result<Transcript, String> {
+        let mut entries = Vec::new();
+
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = || format!("line {}: expected `<step> in|out <value>` or `<step> halt`, found `{}`", n + 1, line);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| words.get(i).and_then(|w| w.parse::<i64>().ok()).ok_or_else(error);
+            let step = words[0].parse::<u64>().map_err(|_| error())?;
+
+            let entry = match (words.get(1).copied(), words.len()) {
+                (Some("in"), 3) => Entry::Input { step, value: number(2)? },
+                (Some("out"), 3) => Entry::Output { step, value: number(2)? },
+                (Some("halt"), 2) => Entry::Halt { step },
+                _ => return Err(error())
+            };
+            entries.push(entry);
+        }
+
+        Ok(Transcript { entries })
+    }
+
+    /// The recorded inputs, in order
+    pub fn inputs(&self) -> impl Iterator<Item=i64> + '_ {
+        self.entries.iter().filter_map(|e| match e {
+            Entry::Input { value, .. } => Some(*value),
+            _ => None
+        })
+    }
+}
+
+/// Run `machine` to completion like `intcode::execute`, adding everything it
+/// reads and writes to `transcript`. Entries up to an error are kept.
+pub fn record<I,O>(machine: &mut Machine, input: &mut I, output: &mut O, transcript: &mut Transcript)
+    -> Result<(), Error> where
+    I: BufRead, O: Write
+{
+    loop {
+        match machine.run()? {
+            Event::Input => {
+                let value = read_value(input, machine.pc())?;
+                // The input instruction runs next
+                transcript.entries.push(Entry::Input { step: machine.steps() + 1, value });
+                machine.push_input(value);
+            },
+            Event::Output(value) => {
+                transcript.entries.push(Entry::Output { step: machine.steps(), value });
+                write_value(output, value)?;
+            },
+            Event::Pause => {},
+            Event::Halt => {
+                transcript.entries.push(Entry::Halt { step: machine.steps() });
+                return Ok(());
+            }
+        };
+    }
+}
+
+/// The first output or halt a replay didn't reproduce
+#[derive(Debug)]
+pub struct Divergence {
+    /// The entry that didn't match, or `None` past the end of the transcript
+    pub expected: Option<Entry>,
+    /// What the program did instead, or the error it stopped with
+    pub actual: Result<Entry, Error>
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.expected {
+            Some(entry) => write!(f, "expected `{}`", entry)?,
+            None => write!(f, "expected the end of the transcript")?
+        };
+        match &self.actual {
+            Ok(entry) => write!(f, ", got `{}`", entry),
+            Err(e) => write!(f, ", got error: {}", e)
+        }
+    }
+}
+
+/// Run `machine` on the inputs recorded in `transcript`, checking its outputs
+/// and halt against the recording. A transcript recorded from a run that
+/// failed matches as long as the replay fails after the same outputs.
+pub fn replay(machine: &mut Machine, transcript: &Transcript) -> Result<(), Divergence> {
+    for value in transcript.inputs() {
+        machine.push_input(value);
+    }
+
+    let mut expected = transcript.entries.iter().filter(|e| !matches!(e, Entry::Input { .. }));
+
+    let failed = |expected: Option<&Entry>, e: Error| match expected {
+        None => Ok(()),
+        Some(entry) => Err(Divergence { expected: Some(*entry), actual: Err(e) })
+    };
+
+    loop {
+        let actual = match machine.run() {
+            Ok(Event::Output(value)) => Entry::Output { step: machine.steps(), value },
+            Ok(Event::Halt) => Entry::Halt { step: machine.steps() },
+            Ok(Event::Pause) => continue,
+            // Every recorded input was queued up front
+            Ok(Event::Input) => return failed(expected.next(), Error::InputExhausted { pc: machine.pc() }),
+            Err(e) => return failed(expected.next(), e)
+        };
+
+        let next = expected.next().copied();
+        if next != Some(actual) {
+            return Err(Divergence { expected: next, actual: Ok(actual) });
+        }
+        if let Entry::Halt { .. } = actual {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{parse_input, InputProvider, OutputCollector};
+
+    fn day5() -> Vec<i64> {
+        parse_input(include_str!("../../input/5.txt")).unwrap()
+    }
+
+    fn recording(program: Vec<i64>, inputs: Vec<i64>) -> Transcript {
+        let mut transcript = Transcript::new();
+        let mut output = OutputCollector::new();
+        record(&mut Machine::new(program), &mut InputProvider::new(inputs), &mut output, &mut transcript).unwrap();
+        transcript
+    }
+
+    #[test]
+    fn records_steps() {
+        let transcript = recording(vec![3,9, 1001,9,1,9, 4,9, 99, 0], vec![41]);
+        assert_eq!(transcript.to_string(), "1 in 41\n3 out 42\n4 halt\n");
+    }
+
+    #[test]
+    fn replays_day5() {
+        for input in &[1, 5] {
+            let transcript = recording(day5(), vec![*input]);
+            let text = transcript.to_string();
+            assert_eq!(Transcript::parse(&text).unwrap(), transcript);
+            assert!(replay(&mut Machine::new(day5()), &transcript).is_ok());
+        }
+    }
+
+    #[test]
+    fn flags_divergence() {
+        // Same inputs, different program
+        let transcript = recording(vec![3,9, 1001,9,1,9, 4,9, 99, 0], vec![41]);
+        let divergence = replay(&mut Machine::new(vec![3,9, 1001,9,2,9, 4,9, 99, 0]), &transcript).unwrap_err();
+        assert_eq!(divergence.to_string(), "expected `3 out 42`, got `3 out 43`");
+
+        let mut transcript = recording(day5(), vec![5]);
+
+        // An output the program no longer makes
+        let step = match transcript.entries[1] { Entry::Output { step, .. } => step, _ => unreachable!() };
+        transcript.entries.insert(2, Entry::Output { step: step + 1, value: 7 });
+        let divergence = replay(&mut Machine::new(day5()), &transcript).unwrap_err();
+        assert_eq!(divergence.to_string(), format!("expected `{} out 7`, got `{} halt`", step + 1, step + 1));
+    }
+
+    #[test]
+    fn failed_recordings() {
+        // The recording ran out of input, and so does the replay
+        let mut transcript = Transcript::new();
+        let result = record(&mut Machine::new(vec![4,0, 3,0, 99]), &mut InputProvider::new(vec![]),
+                            &mut OutputCollector::new(), &mut transcript);
+        assert!(matches!(result, Err(Error::InputExhausted { pc: 2 })));
+        assert!(replay(&mut Machine::new(vec![4,0, 3,0, 99]), &transcript).is_ok());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let text = "# comment\n1 in 5\n2 out\n";
+        assert_eq!(Transcript::parse(text).unwrap_err(),
+                   "line 3: expected `<step> in|out <value>` or `<step> halt`, found `2 out`");
+    }
+}
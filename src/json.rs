@@ -0,0 +1,308 @@
+//! Just enough JSON to save and reload results.
+//!
+//! Numbers are `f64`, so integers are exact only up to 2^53; anything that
+//! might be bigger, like an answer, should be written as a string.
+
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in the order written
+    Object(Vec<(String, Value)>)
+}
+
+impl Value {
+    /// An object from `(key, value)` pairs
+    pub fn object<K: Into<String>>(members: Vec<(K, Value)>) -> Value {
+        Value::Object(members.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// The member `key` of an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(a) => Some(a),
+            _ => None
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Value, String> {
+        let mut parser = Parser { text, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(String::from(s))
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Value {
+        Value::Number(n)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Value {
+        Value::Number(f64::from(n))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Value::Number(_) => write!(f, "null"),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            },
+            Value::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("byte {}: {}", self.pos, message)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        }
+        else {
+            Err(self.error(&format!("expected `{}`", token)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.rest().chars().next() {
+            Some('n') => self.expect("null").map(|_| Value::Null),
+            Some('t') => self.expect("true").map(|_| Value::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(&format!("unexpected `{}`", c))),
+            None => Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let len = self.rest()
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or_else(|| self.rest().len());
+        let n = self.rest()[..len].parse::<f64>().map_err(|_| self.error("invalid number"))?;
+        self.pos += len;
+        Ok(Value::Number(n))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut s = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(s);
+                },
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((j, 'u')) => {
+                            let hex = self.rest().get(j + 1..j + 5).unwrap_or("");
+                            let code = u32::from_str_radix(hex, 16).ok().and_then(std::char::from_u32);
+                            for _ in 0..4 {
+                                chars.next();
+                            }
+                            code.ok_or_else(|| self.error("invalid \\u escape"))?
+                        },
+                        _ => return Err(self.error("invalid escape"))
+                    };
+                    s.push(escaped);
+                },
+                c => s.push(c)
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.expect("]").is_ok() {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            if self.expect("]").is_ok() {
+                return Ok(Value::Array(items));
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect("{")?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.expect("}").is_ok() {
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            if self.expect("}").is_ok() {
+                return Ok(Value::Object(members));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = Value::object(vec![
+            ("day", Value::from(3)),
+            ("answer", Value::from("15612")),
+            ("ok", Value::from(true)),
+            ("times", Value::Array(vec![Value::from(1.5), Value::Null])),
+            ("note", Value::from("a \"quoted\"\nline\u{1}"))
+        ]);
+        let text = value.to_string();
+        assert_eq!(text, r#"{"day":3,"answer":"15612","ok":true,"times":[1.5,null],"note":"a \"quoted\"\nline\u0001"}"#);
+        assert_eq!(Value::parse(&text), Ok(value));
+    }
+
+    #[test]
+    fn parsing() {
+        let value = Value::parse(" { \"a\" : [ 1 , -2.5e3 ] , \"b\" : { } , \"c\": \"\\u00e9\" } ").unwrap();
+        assert_eq!(value.get("a").and_then(Value::as_array).map(|a| a.len()), Some(2));
+        assert_eq!(value.get("a").unwrap().as_array().unwrap()[1].as_f64(), Some(-2500.0));
+        assert_eq!(value.get("c").and_then(Value::as_str), Some("é"));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Value::parse("[1,"), Err(String::from("byte 3: unexpected end of input")));
+        assert_eq!(Value::parse("{\"a\" 1}"), Err(String::from("byte 5: expected `:`")));
+        assert_eq!(Value::parse("\"open"), Err(String::from("byte 1: unterminated string")));
+        assert_eq!(Value::parse("1 2"), Err(String::from("byte 2: trailing characters")));
+    }
+}
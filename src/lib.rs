@@ -1,363 +1,159 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 use structopt::StructOpt;
+#[cfg(feature = "std")]
 use std::path::PathBuf;
+#[cfg(feature = "std")]
+use std::str::FromStr;
+use core::fmt;
+
+#[cfg(feature = "std")]
+mod error;
+#[cfg(feature = "std")]
+pub use crate::error::Error;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SubProblem {
     One,
     Two
 }
 
-/// Advent of Code 2019
-#[derive(StructOpt,Debug)]
-pub struct Opts {
-    /// Path to problem input
-    #[structopt(parse(from_os_str))]
-    input: PathBuf,
-
-    /// Solve alternate problem
-    #[structopt(short, long)]
-    alternate: bool,
-}
-
-
-pub fn input() -> Option<(String, SubProblem)>
-{
-    let args = Opts::from_args();
-
-    let sub = if args.alternate {
-        SubProblem::Two
-    } 
-    else {
-        SubProblem::One
-    };
-
-    match std::fs::read_to_string(args.input) {
-        Ok(s) => Some((s, sub)),
-        Err(e) => {
-            println!("{}", e);
-            None
+impl fmt::Display for SubProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SubProblem::One => f.pad("1"),
+            SubProblem::Two => f.pad("2")
         }
     }
 }
 
-pub mod intcode {
-
-    use std::io::{Read,BufRead,Write,Error,ErrorKind};
-
-    pub struct InputProvider {
-        pub inputs: String
-    }
-    
-    impl InputProvider {
-        pub fn new(inputs: Vec<i64>) -> InputProvider {
-            let str_inputs: Vec<String> = inputs.iter().map(i64::to_string).collect();
-            let mut input: String = str_inputs.join("\n");
-            input.push('\n');
-            InputProvider { inputs: input }
-        }
-    }
-
-    impl Read for InputProvider {
-        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-            if self.inputs.is_empty() {
-                Ok(0)
-            }
-            else {
-                let mut bytes: usize = 0;
-                for i in 0..std::cmp::min(buf.len(), self.inputs.len()) {
-                    let c = self.inputs.remove(0);
-                    buf[i] = c as u8;
-                    bytes += 1;
-                }
-                Ok(bytes)
-            }
-        }
-    }
-
-    impl BufRead for InputProvider {
-        fn fill_buf(&mut self) -> Result<&[u8], Error> {
-            if self.inputs.is_empty() {
-               Err(Error::new(ErrorKind::Other, "Input Exhausted"))
-            }
-            else {
-                Ok(self.inputs.as_bytes())
-            }
-        }
-
-        fn consume(&mut self, amt: usize) {
-            for _ in 0..amt {
-                self.inputs.remove(0);
-            }
-        }
-    }
-
-    pub struct OutputCollector {
-        pub outputs: Vec<i64>
-    }
+/// Which parts of a puzzle to answer: `1`, `2` or `all`
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parts {
+    One,
+    Two,
+    All
+}
 
-    impl OutputCollector {
-        pub fn new() -> OutputCollector {
-            OutputCollector { outputs: Vec::new() }
+#[cfg(feature = "std")]
+impl Parts {
+    pub fn sub_problems(self) -> Vec<SubProblem> {
+        match self {
+            Parts::One => vec![SubProblem::One],
+            Parts::Two => vec![SubProblem::Two],
+            Parts::All => vec![SubProblem::One, SubProblem::Two]
         }
     }
+}
 
-    impl Write for OutputCollector {
-        fn write(&mut self, buf: &[u8]) -> Result<usize,Error> {
-            match std::str::from_utf8(buf) {
-                Ok(s) => {
-                    match s.trim().parse::<i64>() {
-                        Ok(n) => { 
-                            self.outputs.push(n);
-                            Ok(buf.len())
-                        }
-                        Err(_) => Err(Error::new(ErrorKind::Other, "Parse error"))
-                    }
-                },
-                Err(_) => Err(Error::new(ErrorKind::Other, "Parse error"))
-            }
-        }
+#[cfg(feature = "std")]
+impl FromStr for Parts {
+    type Err = String;
 
-        fn flush(&mut self) -> Result<(),Error> {
-            Ok(())
+    fn from_str(s: &str) -> Result<Parts, String> {
+        match s {
+            "1" => Ok(Parts::One),
+            "2" => Ok(Parts::Two),
+            "all" | "both" => Ok(Parts::All),
+            _ => Err(format!("Unknown part '{}', expected 1, 2 or all", s))
         }
     }
+}
 
-    #[derive(Debug)]
-    enum AddressingMode {
-        Immediate,
-        Position
-    }
-
-    #[derive(Debug)]
-    struct Parameter {
-        value: i64,
-        mode: AddressingMode
-    }
-
-    #[derive(Debug)]
-    enum OutputParameterType {
-        Write,
-        Jump
-    }
+/// How to print results: `table` for people, `json` for programs
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Table,
+    Json
+}
 
-    #[derive(Debug)]
-    enum Instruction {
-        Add(Parameter, Parameter, Parameter),
-        Multiply(Parameter, Parameter, Parameter),
-        Finish,
-        Input(Parameter),
-        Output(Parameter),
-        JumpNonZero(Parameter, Parameter),
-        JumpZero(Parameter, Parameter),
-        LessThan(Parameter, Parameter, Parameter),
-        Equals(Parameter, Parameter, Parameter)
-    }
+#[cfg(feature = "std")]
+impl FromStr for Format {
+    type Err = String;
 
-    impl Instruction {
-        fn arg_count(command: &i64) -> usize {
-            match command % 100 {
-                1 => 3,
-                2 => 3,
-                3 => 1,
-                4 => 1,
-                5 => 2,
-                6 => 2,
-                7 => 3,
-                8 => 3,
-                99 => 0,
-                n => panic!("Unexpected opcode: {}", n)
-            }
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("Unknown format '{}', expected table or json", s))
         }
     }
+}
 
-    fn decode_args<'a, T>(mut program: T, opcode: &i64, count: usize) -> Vec<Parameter>
-        where T: Iterator<Item=&'a i64> 
-    { 
-        let mut args = Vec::new();
-        let mut opcode = opcode.clone();
-
-        opcode /= 100;
-
-        for _ in 0..count {
-            let mode = match opcode % 10 { 
-                0 => AddressingMode::Position,
-                1 => AddressingMode::Immediate,
-                n => panic!("Unexpected Addressing Mode: {}", n)
-            };
-
-            let param = Parameter {
-                mode: mode,
-                value: program.next().expect("Program ended during argument decode").clone()
-            };
-
-            args.push(param);
-
-            opcode /= 10;
-        }
-
-        args
-    }
+/// Advent of Code 2019
+#[cfg(feature = "std")]
+#[derive(StructOpt,Debug)]
+pub struct Opts {
+    /// Path to problem input, or `-` for stdin [default: <input-dir>/<day>.txt]
+    #[structopt(parse(from_os_str))]
+    input: Option<PathBuf>,
 
-    fn decode<'a, T>(mut program: T) -> Option<Instruction> 
-        where T: Iterator<Item=&'a i64> 
-    { 
-        let opcode = program.next()?;
-        let mut args = decode_args(program, opcode, Instruction::arg_count(&opcode));
-        let mut dargs = args.drain(..);
+    /// Directory of inputs named by day
+    #[structopt(long, env = "AOC_INPUT_DIR", default_value = inputs::DEFAULT_DIR, parse(from_os_str))]
+    input_dir: PathBuf,
 
-        match opcode % 100 {
-            1=> {
-                let a = dargs.next().unwrap();
-                let b = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::Add(a, b, o))
-            },
-            2 => {
-                let a = dargs.next().unwrap();
-                let b = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::Multiply(a, b, o))
-            },
-            3 => Some(Instruction::Input(dargs.next().unwrap())),
-            4 => Some(Instruction::Output(dargs.next().unwrap())),
-            5 => {
-                let i = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::JumpNonZero(i, o))
-            },
-            6 => {
-                let i = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::JumpZero(i, o))
-            },
-            7 => {
-                let a = dargs.next().unwrap();
-                let b = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::LessThan(a, b, o))
-            },
-            8 => {
-                let a = dargs.next().unwrap();
-                let b = dargs.next().unwrap();
-                let o = dargs.next().unwrap();
-                Some(Instruction::Equals(a, b, o))
-            },
-            99 => Some(Instruction::Finish),
-            n => panic!("Unexpected command: {}", n)
-        }
-    }
+    /// Part to answer: 1, 2 or all [default: 1]
+    #[structopt(short, long)]
+    part: Option<Parts>,
 
-    fn input(program: &Vec<i64>, param: &Parameter) -> i64 {
-        match param.mode {
-            AddressingMode::Immediate => param.value,
-            AddressingMode::Position => program[param.value as usize]
-        }
-    }
+    /// Same as `--part 2`
+    #[structopt(short, long, conflicts_with = "part")]
+    alternate: bool,
 
-    fn output_index(program: &Vec<i64>, param: &Parameter, param_type: &OutputParameterType) -> usize {
-        let index = param.value as usize;
-        match param_type {
-            OutputParameterType::Jump => {
-                match param.mode {
-                    AddressingMode::Immediate => index,
-                    AddressingMode::Position => program[index] as usize
-                }
-            },
-            OutputParameterType::Write => index
-        }
-    }
+    /// Output format: table or json
+    #[structopt(long, default_value = "table")]
+    format: Format,
+}
 
-    fn execute_instruction<I,O>(program: &mut Vec<i64>, instruction: &Instruction, pc: &mut usize, is: &mut I, os: &mut O) where
-        I: BufRead, O: Write {
-        match instruction {
-            Instruction::Add(a,b,o) => {
-                let a = input(program, a);
-                let b = input(program, b);
-                let o = output_index(program, o, &OutputParameterType::Write);
-                program[o] = a + b;
-                *pc = *pc + 4;
-            },
-            Instruction::Multiply(a, b, o) => {
-                let a = input(program, a);
-                let b = input(program, b);
-                let o = output_index(program, o, &OutputParameterType::Write);
-                program[o] = a * b;
-                *pc = *pc + 4;
-            },
-            Instruction::Input(o) => {
-                let mut input_data = String::new();
-                is.read_line(&mut input_data).unwrap();
-                let o = o.value as usize;
-                let v = input_data.trim().parse::<i64>().unwrap();
-                program[o] = v;
-                *pc = *pc + 2;
-            },
-            Instruction::Output(i) => {
-                write!(os, "{}", input(program, i)).unwrap();
-                os.flush().unwrap();
-                *pc = *pc + 2;
-            },
-            Instruction::JumpNonZero(i, o) => {
-                if input(program, i) != 0 {
-                    *pc = output_index(program, o, &OutputParameterType::Jump);
-                }
-                else {
-                    *pc = *pc + 3;
-                }
-            },
-            Instruction::JumpZero(i, o) => {
-                if input(program, i) == 0 {
-                    *pc = output_index(program, o, &OutputParameterType::Jump);
-                }
-                else {
-                    *pc = *pc + 3;
-                }
-            },
-            Instruction::LessThan(a, b, o) => {
-                let a = input(program, a);
-                let b = input(program, b);
-                let o = output_index(program, o, &OutputParameterType::Write);
 
-                program[o] = if a < b { 1 } else { 0 };
-                *pc = *pc + 4;
-            },
-            Instruction::Equals(a, b, o) => {
-                let a = input(program, a);
-                let b = input(program, b);
-                let o = output_index(program, o, &OutputParameterType::Write);
+#[cfg(feature = "std")]
+pub fn input(day: u32) -> Result<(String, Parts, Format), Error>
+{
+    let args = Opts::from_args();
 
-                program[o] = if a == b { 1 } else { 0 };
-                *pc = *pc + 4;
-            },
-            Instruction::Finish => {
-                *pc = *pc + 1;
-            }
-        };
+    let parts = if args.alternate {
+        Parts::Two
     }
+    else {
+        args.part.unwrap_or(Parts::One)
+    };
 
+    let path = inputs::resolve(args.input, &args.input_dir, day);
+    Ok((inputs::read(&path)?, parts, args.format))
+}
 
-    pub fn execute<I,O>(program: &mut Vec<i64>, input: &mut I, output: &mut O) where
-        I: BufRead, O: Write
-    {
-        let mut pc: usize = 0;
-        while pc < program.len() {
-
-            match decode(program.iter().skip(pc)) {
-                Some(Instruction::Finish) | None => {
-                    break
-                },
-                Some(instruction) => {
-                    execute_instruction(program, &instruction, &mut pc, input, output);
-                }
-            };
-        }
-    }
-
-    pub fn parse_input(input: String) -> Vec<i64> {
-        input.split(',')
-             .map(|op| {
-                 op.parse::<i64>().unwrap()
-             })
-             .collect()
-    }
-}
\ No newline at end of file
+pub mod intcode;
+#[cfg(feature = "std")]
+pub mod answers;
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "std")]
+pub mod inputs;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod scaffold;
+#[cfg(feature = "std")]
+pub mod solution;
+
+#[cfg(feature = "std")]
+pub mod day1;
+#[cfg(feature = "std")]
+pub mod day2;
+#[cfg(feature = "std")]
+pub mod day3;
+#[cfg(feature = "std")]
+pub mod day4;
+#[cfg(feature = "std")]
+pub mod day5;
+#[cfg(feature = "std")]
+pub mod day6;
+#[cfg(feature = "std")]
+pub mod day7;
@@ -0,0 +1,228 @@
+//! Adding a new day: its solver module, registry entry and binary.
+//!
+//! `create` writes `src/day<N>.rs` with a placeholder `Solution` and an
+//! example test, a binary shim, an empty input file, and adds the day to
+//! `lib.rs`, `DAYS` and `Cargo.toml` in order by day number. Everything is
+//! prepared before anything is written, so a failure leaves the tree alone.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::Error;
+
+fn module(day: u32) -> String {
+    format!(r#"use crate::solution::Solution;
+
+/// TODO: parse day {day}'s input into something to solve
+pub fn parse(input: &str) -> Result<Vec<String>, String> {{
+    Ok(input.lines().map(String::from).collect())
+}}
+
+/// TODO: what the puzzle input describes
+pub struct Puzzle {{
+    lines: Vec<String>
+}}
+
+impl Solution for Puzzle {{
+    type Answer = usize;
+    const PART2: bool = false;
+
+    fn parse(input: &str) -> Result<Puzzle, String> {{
+        Ok(Puzzle {{ lines: parse(input)? }})
+    }}
+
+    fn part1(&self) -> Result<usize, String> {{
+        // TODO: solve part one
+        Ok(self.lines.len())
+    }}
+
+    fn part2(&self) -> Result<usize, String> {{
+        // TODO: solve part two, and drop PART2 above
+        unreachable!("Part 2 isn't solved")
+    }}
+}}
+
+#[cfg(test)]
+mod tests {{
+    use super::*;
+
+    #[test]
+    fn example() {{
+        // TODO: the puzzle's worked example and its answer
+        let puzzle = Puzzle::parse("").unwrap();
+        assert_eq!(puzzle.part1(), Ok(0));
+    }}
+}}
+"#, day = day)
+}
+
+fn shim(day: u32) -> String {
+    format!("extern crate aoc;\n\nfn main() {{\n    aoc::solution::main({})\n}}\n", day)
+}
+
+/// The day number in `line` if it's `prefix` then a number then `suffix`
+fn day_in(line: &str, prefix: &str, suffix: &str) -> Option<u32> {
+    line.trim().strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()
+}
+
+/// Where each day has a block of lines in a file: `lead` lines, then the
+/// line `day` finds its number in, then `tail` lines
+struct Blocks<F> {
+    day: F,
+    lead: usize,
+    tail: usize
+}
+
+impl<F: Fn(&str) -> Option<u32>> Blocks<F> {
+    /// Insert `block` after the block of the latest day before `day`, or
+    /// before the first day's. `Err` if there are no days, or `day` is
+    /// already there.
+    fn insert(&self, text: &str, day: u32, block: &str) -> Result<String, String> {
+        let numbered: Vec<(usize, u32)> = text.lines().enumerate()
+            .filter_map(|(i, line)| (self.day)(line).map(|d| (i, d)))
+            .collect();
+
+        if numbered.iter().any(|&(_, d)| d == day) {
+            return Err(format!("day {} is already there", day));
+        }
+        let at = match (numbered.iter().filter(|&&(_, d)| d < day).max_by_key(|&&(_, d)| d), numbered.first()) {
+            (Some(&(i, _)), _) => i + self.tail + 1,
+            (None, Some(&(i, _))) => i.saturating_sub(self.lead),
+            (None, None) => return Err(String::from("no days to add to"))
+        };
+
+        let mut lines: Vec<&str> = text.lines().collect();
+        lines.splice(at..at, block.lines());
+        Ok(lines.join("\n") + "\n")
+    }
+}
+
+/// `lib.rs` with `pub mod day<N>;` added
+fn add_module(lib: &str, day: u32) -> Result<String, String> {
+    let blocks = Blocks { day: |l: &str| day_in(l, "pub mod day", ";"), lead: 1, tail: 0 };
+    blocks.insert(lib, day, &format!("#[cfg(feature = \"std\")]\npub mod day{};", day))
+}
+
+/// `solution.rs` with the day in `DAYS`
+fn add_registry(solution: &str, day: u32, title: &str) -> Result<String, String> {
+    let entry = |l: &str| l.trim().strip_prefix("Day::new::<crate::day")
+        .and_then(|rest| rest.split("::").next())
+        .and_then(|n| n.parse().ok());
+
+    let blocks = Blocks { day: entry, lead: 0, tail: 0 };
+    let text = blocks.insert(solution, day, &format!("    Day::new::<crate::day{}::Puzzle>({}, {:?})", day, day, title))?;
+
+    // Every entry but the last ends with a comma
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+    let entries: Vec<usize> = (0..lines.len()).filter(|&i| entry(&lines[i]).is_some()).collect();
+    for &i in &entries {
+        let bare = lines[i].trim_end().trim_end_matches(',').to_string();
+        lines[i] = if Some(&i) == entries.last() { bare } else { bare + "," };
+    }
+
+    // And the array is one longer
+    let header = lines.iter().position(|l| l.starts_with("pub const DAYS: [Day; "))
+        .ok_or_else(|| String::from("can't find DAYS"))?;
+    lines[header] = format!("pub const DAYS: [Day; {}] = [", entries.len());
+
+    Ok(lines.join("\n") + "\n")
+}
+
+/// `Cargo.toml` with a `[[bin]]` for the day
+fn add_binary(manifest: &str, day: u32) -> Result<String, String> {
+    // Each is `[[bin]]`, the name, its features and a blank line
+    let blocks = Blocks { day: |l: &str| day_in(l, "name = \"day", "\""), lead: 1, tail: 2 };
+    blocks.insert(manifest, day, &format!("[[bin]]\nname = \"day{}\"\nrequired-features = [\"std\"]\n\n", day))
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    std::fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))
+}
+
+/// Scaffold day `day` in the crate at `root`, returning the files written
+pub fn create(root: &Path, day: u32, title: &str) -> Result<Vec<PathBuf>, Error> {
+    let edit = |file: &str, f: &dyn Fn(&str) -> Result<String, String>| -> Result<(PathBuf, String), Error> {
+        let path = root.join(file);
+        let text = f(&read(&path)?).map_err(|e| Error::Parse(format!("{}: {}", path.display(), e)))?;
+        Ok((path, text))
+    };
+
+    let mut files = vec![
+        (root.join(format!("src/day{}.rs", day)), module(day)),
+        (root.join(format!("src/bin/day{}.rs", day)), shim(day)),
+        (root.join(format!("input/{}.txt", day)), String::new())
+    ];
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(Error::Io(path.clone(), io::Error::new(io::ErrorKind::AlreadyExists, "already exists")));
+    }
+
+    files.push(edit("src/lib.rs", &|text| add_module(text, day))?);
+    files.push(edit("src/solution.rs", &|text| add_registry(text, day, title))?);
+    files.push(edit("Cargo.toml", &|text| add_binary(text, day))?);
+
+    for (path, text) in &files {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| Error::Io(dir.to_path_buf(), e))?;
+        }
+        std::fs::write(path, text).map_err(|e| Error::Io(path.clone(), e))?;
+    }
+    Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modules() {
+        let lib = "pub mod intcode;\n#[cfg(feature = \"std\")]\npub mod day1;\n#[cfg(feature = \"std\")]\npub mod day3;\n";
+        assert_eq!(add_module(lib, 4).unwrap(), String::from(lib) + "#[cfg(feature = \"std\")]\npub mod day4;\n");
+        assert_eq!(add_module(lib, 2).unwrap(),
+                   "pub mod intcode;\n#[cfg(feature = \"std\")]\npub mod day1;\n#[cfg(feature = \"std\")]\npub mod day2;\n#[cfg(feature = \"std\")]\npub mod day3;\n");
+        assert_eq!(add_module(lib, 0).unwrap(),
+                   "pub mod intcode;\n#[cfg(feature = \"std\")]\npub mod day0;\n#[cfg(feature = \"std\")]\npub mod day1;\n#[cfg(feature = \"std\")]\npub mod day3;\n");
+        assert_eq!(add_module(lib, 3), Err(String::from("day 3 is already there")));
+        assert_eq!(add_module("pub mod intcode;\n", 3), Err(String::from("no days to add to")));
+    }
+
+    #[test]
+    fn registry() {
+        let solution = "pub const DAYS: [Day; 2] = [\n    Day::new::<crate::day1::Modules>(1, \"One\"),\n    Day::new::<crate::day2::Alarm>(2, \"Two\")\n];\n";
+        assert_eq!(add_registry(solution, 8, "Space Image Format").unwrap(),
+                   "pub const DAYS: [Day; 3] = [\n    Day::new::<crate::day1::Modules>(1, \"One\"),\n    Day::new::<crate::day2::Alarm>(2, \"Two\"),\n    Day::new::<crate::day8::Puzzle>(8, \"Space Image Format\")\n];\n");
+        assert!(add_registry(solution, 2, "Again").is_err());
+    }
+
+    #[test]
+    fn binaries() {
+        let manifest = "[[bin]]\nname = \"aoc\"\n\n[[bin]]\nname = \"day1\"\nrequired-features = [\"std\"]\n\n[[bin]]\nname = \"day3\"\nrequired-features = [\"std\"]\n\n[[bin]]\nname = \"intcc\"\n";
+        let bin = |day| format!("[[bin]]\nname = \"day{}\"\nrequired-features = [\"std\"]\n\n", day);
+
+        assert_eq!(add_binary(manifest, 2).unwrap(), manifest.replace("[[bin]]\nname = \"day3\"", &(bin(2) + "[[bin]]\nname = \"day3\"")));
+        assert_eq!(add_binary(manifest, 4).unwrap(), manifest.replace("[[bin]]\nname = \"intcc\"", &(bin(4) + "[[bin]]\nname = \"intcc\"")));
+    }
+
+    #[test]
+    fn creating() {
+        let root = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        for file in &["Cargo.toml", "src/lib.rs", "src/solution.rs"] {
+            std::fs::copy(manifest.join(file), root.join(file)).unwrap();
+        }
+
+        // A day after any there'll be
+        let written = create(&root, 99, "Beyond Christmas").unwrap();
+        assert_eq!(written.len(), 6);
+        assert!(written.iter().all(|path| path.starts_with(&root)));
+        assert!(read(&root.join("src/solution.rs")).unwrap().contains("Day::new::<crate::day99::Puzzle>(99, \"Beyond Christmas\")\n];"));
+        assert_eq!(read(&root.join("input/99.txt")).unwrap(), "");
+
+        // Nothing is touched the second time
+        let lib = read(&root.join("src/lib.rs")).unwrap();
+        assert!(matches!(create(&root, 99, "Again"), Err(Error::Io(..))));
+        assert_eq!(read(&root.join("src/lib.rs")).unwrap(), lib);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
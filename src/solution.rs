@@ -0,0 +1,222 @@
+//! Solutions to each day's puzzle, behind one interface.
+//!
+//! A day's puzzle input is parsed once into a type implementing `Solution`,
+//! which then answers either part. `DAYS` lists every solved day with its
+//! types erased, so a runner can pick one by number.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::json::Value;
+use crate::{bench, inputs, Error, Format, Parts, SubProblem};
+
+pub trait Solution: Sized {
+    type Answer: fmt::Display;
+
+    /// Whether whitespace at the end of input lines can be dropped
+    const TRIM: bool = true;
+    /// Whether `part2` is implemented; if not it's never called
+    const PART2: bool = true;
+
+    fn parse(input: &str) -> Result<Self, String>;
+    fn part1(&self) -> Result<Self::Answer, String>;
+    fn part2(&self) -> Result<Self::Answer, String>;
+}
+
+/// `input` as `S` should see it
+pub(crate) fn prepare<S: Solution>(input: &str) -> Cow<'_, str> {
+    if S::TRIM {
+        Cow::Owned(inputs::trim_trailing(input))
+    }
+    else {
+        Cow::Borrowed(input)
+    }
+}
+
+/// Parse `input` as `S` and answer `part`
+fn solve<S: Solution>(input: &str, part: &SubProblem) -> Result<String, Error> {
+    if *part == SubProblem::Two && !S::PART2 {
+        return Err(Error::NotImplemented(*part));
+    }
+
+    let puzzle = S::parse(&prepare::<S>(input)).map_err(Error::Parse)?;
+    let answer = match part {
+        SubProblem::One => puzzle.part1(),
+        SubProblem::Two => puzzle.part2()
+    };
+    answer.map(|a| a.to_string()).map_err(Error::NoSolution)
+}
+
+/// One part's answer, or why there isn't one, and how long it took
+#[derive(Debug)]
+pub struct Report {
+    pub day: u32,
+    pub part: SubProblem,
+    pub answer: Result<String, Error>,
+    pub time: Duration
+}
+
+impl Report {
+    pub fn to_json(&self) -> Value {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (Value::from(answer.as_str()), Value::Null),
+            Err(e) => (Value::Null, Value::from(e.to_string()))
+        };
+        Value::object(vec![
+            ("day", Value::from(self.day)),
+            ("part", Value::from(self.part.to_string())),
+            ("answer", answer),
+            ("time_ns", Value::Number(self.time.as_nanos() as f64)),
+            ("error", error)
+        ])
+    }
+}
+
+pub struct Day {
+    pub number: u32,
+    pub title: &'static str,
+    part2: bool,
+    solve: fn(&str, &SubProblem) -> Result<String, Error>,
+    bench: fn(&str, &bench::Config) -> Result<bench::Timings, Error>
+}
+
+impl Day {
+    const fn new<S: Solution>(number: u32, title: &'static str) -> Day {
+        Day { number, title, part2: S::PART2, solve: solve::<S>, bench: bench::measure::<S> }
+    }
+
+    pub fn implements(&self, part: &SubProblem) -> bool {
+        *part == SubProblem::One || self.part2
+    }
+
+    /// The parts to answer when asked for `parts`: just the one named, or
+    /// every part implemented for `Parts::All`
+    pub fn parts(&self, parts: Parts) -> Vec<SubProblem> {
+        parts.sub_problems().into_iter().filter(|p| parts != Parts::All || self.implements(p)).collect()
+    }
+
+    pub fn solve(&self, input: &str, part: &SubProblem) -> Result<String, Error> {
+        (self.solve)(input, part)
+    }
+
+    /// Solve `part`, timing it
+    pub fn report(&self, input: &str, part: &SubProblem) -> Report {
+        let start = Instant::now();
+        let answer = self.solve(input, part);
+        Report { day: self.number, part: *part, answer, time: start.elapsed() }
+    }
+
+    /// Time parsing `input` and each part
+    pub fn bench(&self, input: &str, config: &bench::Config) -> Result<Vec<bench::Measurement>, Error> {
+        Ok((self.bench)(input, config)?.into_iter()
+            .map(|(stage, stats)| bench::Measurement { day: self.number, stage, stats })
+            .collect())
+    }
+}
+
+impl fmt::Debug for Day {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Day {}: {}", self.number, self.title)
+    }
+}
+
+pub const DAYS: [Day; 7] = [
+    Day::new::<crate::day1::Modules>(1, "The Tyranny of the Rocket Equation"),
+    Day::new::<crate::day2::Alarm>(2, "1202 Program Alarm"),
+    Day::new::<crate::day3::Wires>(3, "Crossed Wires"),
+    Day::new::<crate::day4::Passwords>(4, "Secure Container"),
+    Day::new::<crate::day5::Diagnostic>(5, "Sunny with a Chance of Asteroids"),
+    Day::new::<crate::day6::Orbits>(6, "Universal Orbit Map"),
+    Day::new::<crate::day7::Amplifiers>(7, "Amplification Circuit")
+];
+
+pub fn day(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|d| d.number == number)
+}
+
+/// Solve `parts` and print them, then exit. As a table, a lone part's
+/// answer is printed bare and several are labelled, stopping at the first
+/// error. As JSON, every part is reported before exiting with the first
+/// error's code.
+pub fn print(day: &Day, input: &str, parts: &[SubProblem], format: Format) -> ! {
+    match format {
+        Format::Table => {
+            for part in parts {
+                match (day.solve(input, part), parts.len()) {
+                    (Ok(answer), 1) => println!("{}", answer),
+                    (Ok(answer), _) => println!("Part {}: {}", part, answer),
+                    (Err(e @ Error::NotImplemented(_)), _) | (Err(e), 1) => e.exit(),
+                    (Err(e), _) => {
+                        eprint!("Part {}: ", part);
+                        e.exit()
+                    }
+                }
+            }
+        },
+        Format::Json => {
+            let reports: Vec<Report> = parts.iter().map(|part| day.report(input, part)).collect();
+            println!("{}", Value::Array(reports.iter().map(Report::to_json).collect()));
+
+            if let Some(e) = reports.iter().find_map(|r| r.answer.as_ref().err()) {
+                std::process::exit(e.exit_code());
+            }
+        }
+    }
+    std::process::exit(0);
+}
+
+/// The whole of a day's binary: solve the parts asked for on the command
+/// line and print each answer, or report why not
+pub fn main(number: u32) -> ! {
+    let day = day(number).unwrap_or_else(|| panic!("Day {} isn't registered", number));
+
+    let (input, parts, format) = crate::input(number).unwrap_or_else(|e| e.exit());
+    print(day, &input, &day.parts(parts), format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        for (i, d) in DAYS.iter().enumerate() {
+            assert_eq!(d.number as usize, i + 1);
+        }
+        assert!(day(0).is_none());
+        assert_eq!(day(4).map(|d| d.title), Some("Secure Container"));
+    }
+
+    #[test]
+    fn solve_parts() {
+        let day = day(1).unwrap();
+        assert_eq!(day.solve("12\n1969\n", &SubProblem::One).unwrap(), "656");
+        assert_eq!(day.solve("12\n1969\n", &SubProblem::Two).unwrap(), "968");
+        assert!(matches!(day.solve("twelve", &SubProblem::One), Err(Error::Parse(_))));
+
+        let unsolved = super::day(6).unwrap().solve("COM)B\n", &SubProblem::Two);
+        assert!(matches!(unsolved, Err(Error::NotImplemented(SubProblem::Two))));
+    }
+
+    #[test]
+    fn parts() {
+        assert_eq!(day(1).unwrap().parts(Parts::All), vec![SubProblem::One, SubProblem::Two]);
+        assert_eq!(day(6).unwrap().parts(Parts::All), vec![SubProblem::One]);
+        assert_eq!(day(6).unwrap().parts(Parts::Two), vec![SubProblem::Two]);
+    }
+
+    #[test]
+    fn reports() {
+        let report = day(1).unwrap().report("12\n", &SubProblem::One);
+        let json = report.to_json();
+        assert_eq!(json.get("answer").and_then(Value::as_str), Some("2"));
+        assert_eq!(json.get("part").and_then(Value::as_str), Some("1"));
+        assert_eq!(json.get("error"), Some(&Value::Null));
+
+        let failed = day(3).unwrap().report("R8\n", &SubProblem::Two).to_json();
+        assert_eq!(failed.get("answer"), Some(&Value::Null));
+        assert_eq!(failed.get("error").and_then(Value::as_str), Some("Invalid input: Second input line not provided."));
+        assert!(failed.get("time_ns").and_then(Value::as_f64).is_some());
+    }
+}
@@ -0,0 +1,21 @@
+//! Every solution still gives the answers in `answers.txt`.
+
+extern crate aoc;
+
+use std::path::Path;
+use aoc::answers::{self, Status};
+use aoc::solution::DAYS;
+
+#[test]
+fn known_answers() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(answers::DEFAULT_FILE);
+    let expected = answers::load(&path).unwrap();
+
+    let failures: Vec<String> = answers::verify(&DAYS, &expected).into_iter()
+        .filter_map(|c| match c.status {
+            Status::Fail(why) => Some(format!("day {} part {}: {}", c.day, c.part, why)),
+            _ => None
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
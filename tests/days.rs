@@ -0,0 +1,68 @@
+//! Each day's solver through the library's public API, on the puzzles'
+//! worked examples.
+
+extern crate aoc;
+
+use aoc::{day1, day2, day3, day4, day5, day6, day7};
+
+#[test]
+fn day1_fuel() {
+    let masses = day1::parse("12\n14\n1969\n100756\n").unwrap();
+    assert_eq!(day1::fuel_sum(&masses), 2 + 2 + 654 + 33583);
+    assert_eq!(day1::fuel_sum_alt(&masses), 2 + 2 + 966 + 50346);
+    assert!(day1::parse("12\nfourteen\n").is_err());
+}
+
+#[test]
+fn day2_program() {
+    // Address 0 ends up as noun * verb
+    let program = day2::parse("2,0,0,0,99").unwrap();
+    assert_eq!(day2::run(&program, 4, 0).unwrap(), 2 * 99);
+    assert!(day2::parse("1,0").is_err());
+}
+
+#[test]
+fn day3_wires() {
+    let (a, b) = day3::parse("R8,U5,L5,D3\nU7,R6,D4,L4\n").unwrap();
+    assert_eq!(day3::collisions(&a, &b).len(), 2);
+    assert_eq!(day3::closest_collision(&day3::collisions(&a, &b)), Some(6));
+    assert_eq!(day3::minimum_steps_to_collision(&a, &b), Some(30));
+    assert!(day3::parse("R8,U5\n").is_err());
+}
+
+#[test]
+fn day4_passwords() {
+    assert_eq!(day4::parse("111111-111123\n"), Ok((111111, 111123)));
+    assert!(day4::valid_password(111111));
+    assert!(!day4::valid_password(223450));
+    assert!(!day4::valid_password(123789));
+    assert!(day4::valid_alternate_password(112233));
+    assert!(!day4::valid_alternate_password(123444));
+    assert!(day4::valid_alternate_password(111122));
+}
+
+#[test]
+fn day5_diagnostic() {
+    // Outputs 1 if the input is equal to 8, or 0 if it isn't
+    let program = day5::parse("3,9,8,9,10,9,4,9,99,-1,8").unwrap();
+    assert_eq!(day5::diagnose(&program, 8), Ok(1));
+    assert_eq!(day5::diagnose(&program, 5), Ok(0));
+}
+
+#[test]
+fn day6_orbits() {
+    let orbits = day6::parse("COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\n").unwrap();
+    assert_eq!(orbits.len(), 11);
+    assert_eq!(day6::orbit_count(&day6::create_orbit_tree(orbits)), 42);
+}
+
+#[test]
+fn day7_amplifiers() {
+    assert_eq!(day7::phase_permutations(3).len(), 6);
+    let program = day7::parse("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0").unwrap();
+    assert_eq!(day7::max_signal(&program).unwrap(), 43210);
+
+    // An amplifier that outputs nothing has no signal to pass on
+    let silent = day7::parse("3,5,3,5,99,0").unwrap();
+    assert_eq!(day7::max_signal(&silent), Err(String::from("No output from the amplifier with phase 0")));
+}
@@ -0,0 +1,23 @@
+//! Builds the library without the `std` feature, as an embedded user would.
+//!
+//! It goes in its own target directory so it doesn't wait on the lock held
+//! by the build running this.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn builds_without_std() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+
+    let output = Command::new(cargo)
+        .current_dir(&root)
+        .env("CARGO_TARGET_DIR", root.join("target/no-std"))
+        .env("RUSTFLAGS", "-D warnings")
+        .args(["build", "--no-default-features"])
+        .output().expect("Couldn't run cargo");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}